@@ -1,9 +1,11 @@
 #include "ruby/ruby.h"
+#include "ruby/encoding.h"
 #include "stdint.h"
 
 VALUE RS_Qtrue = Qtrue;
 VALUE RS_Qfalse = Qfalse;
 VALUE RS_Qnil = Qnil;
+VALUE RS_Qundef = Qundef;
 
 // begin ruby_value_type
 int RS_T_NONE = RUBY_T_NONE;
@@ -37,6 +39,11 @@ int RS_T_ICLASS = RUBY_T_ICLASS;
 int RS_T_ZOMBIE = RUBY_T_ZOMBIE;
 // end ruby_value_type
 
+int RS_ENC_CODERANGE_UNKNOWN = ENC_CODERANGE_UNKNOWN;
+int RS_ENC_CODERANGE_7BIT = ENC_CODERANGE_7BIT;
+int RS_ENC_CODERANGE_VALID = ENC_CODERANGE_VALID;
+int RS_ENC_CODERANGE_BROKEN = ENC_CODERANGE_BROKEN;
+
 int RS_RB_TYPE_P(VALUE obj, int type) { return RB_TYPE_P(obj, type); }
 
 VALUE RS_CLASS_OF(VALUE v) { return CLASS_OF(v); }
@@ -68,6 +75,18 @@ long RS_RSTRING_LEN(VALUE str) { return RSTRING_LEN(str); }
 // but this seems quite risky, so we'll call it a `const`.
 const char* RS_RSTRING_PTR(VALUE str) { return RSTRING_PTR(str); }
 
+// The mutable version, for filling strings in place. The string must have
+// gone through `rb_str_modify` (or `rb_str_modify_expand`) first.
+char* RS_RSTRING_PTR_MUT(VALUE str) { return RSTRING_PTR(str); }
+
 long RS_RARRAY_LEN(VALUE a) { return RARRAY_LEN(a); }
 
 size_t RS_RHASH_SIZE(VALUE h) { return RHASH_SIZE(h); }
+
+int RS_RTYPEDDATA_P(VALUE v) { return RTYPEDDATA_P(v); }
+const rb_data_type_t* RS_RTYPEDDATA_TYPE(VALUE v) { return RTYPEDDATA_TYPE(v); }
+void* RS_RTYPEDDATA_DATA(VALUE v) { return RTYPEDDATA_DATA(v); }
+
+const char* RS_rb_enc_name(rb_encoding* enc) { return rb_enc_name(enc); }
+int RS_rb_enc_mbmaxlen(rb_encoding* enc) { return rb_enc_mbmaxlen(enc); }
+int RS_rb_enc_mbminlen(rb_encoding* enc) { return rb_enc_mbminlen(enc); }
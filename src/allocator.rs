@@ -0,0 +1,187 @@
+use super::*;
+use libc::{self, c_void, ssize_t};
+use std::alloc::{GlobalAlloc, Layout};
+use std::mem::align_of;
+use std::ptr::null_mut;
+
+// The alignment assumed of `malloc`. Platforms usually guarantee more, but
+// this is all that is needed to store the pointer of over-aligned allocations.
+const MIN_ALIGN: usize = align_of::<usize>();
+
+/// A [`GlobalAlloc`] whose allocations are reported to Ruby's GC with
+/// [`rb_gc_adjust_memory_usage`], so that Rust allocations count towards
+/// Ruby's malloc-triggered garbage collection (e.g.
+/// `GC.stat(:malloc_increase_bytes)`).
+///
+/// Only available with the `global_allocator` feature, which needs Ruby 2.4+
+/// for `rb_gc_adjust_memory_usage`. It must be installed explicitly:
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: RubyAllocator = RubyAllocator;
+/// ```
+///
+/// # Safety
+///
+/// * Ruby must be initialized before the first allocation, which is always
+/// the case for extensions loaded by Ruby.
+///
+/// # Miscellaneous
+///
+/// * Memory comes from the C library's `malloc` rather than
+/// [`ruby_xmalloc`], which raises `NoMemoryError` instead of returning null
+/// and may start a garbage collection, neither of which is allowed from a
+/// `GlobalAlloc`, in particular on threads not created by Ruby. The usage
+/// reports never start a garbage collection themselves; the GC takes them
+/// into account the next time Ruby allocates.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RubyAllocator;
+
+impl RubyAllocator {
+    fn over_aligned(layout: Layout) -> bool {
+        layout.align() > MIN_ALIGN
+    }
+
+    // Over-aligned allocations get `align` extra bytes, and the pointer to
+    // the start of the allocation is stored just before the aligned pointer.
+    unsafe fn alloc_over_aligned(layout: Layout, zeroed: bool) -> *mut u8 {
+        let size = match layout.size().checked_add(layout.align()) {
+            Some(size) => size,
+            None => return null_mut(),
+        };
+
+        let raw = if zeroed { libc::calloc(1, size) } else { libc::malloc(size) } as *mut u8;
+
+        if raw.is_null() {
+            return raw;
+        }
+
+        let aligned = raw.add(layout.align() - (raw as usize % layout.align()));
+
+        *(aligned as *mut *mut u8).sub(1) = raw;
+
+        aligned
+    }
+
+    fn report(diff: isize, ptr: *mut u8) -> *mut u8 {
+        if !ptr.is_null() {
+            unsafe { rb_gc_adjust_memory_usage(diff as ssize_t) };
+        }
+
+        ptr
+    }
+}
+
+unsafe impl GlobalAlloc for RubyAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = if Self::over_aligned(layout) {
+            Self::alloc_over_aligned(layout, false)
+        } else {
+            libc::malloc(layout.size()) as *mut u8
+        };
+
+        Self::report(layout.size() as isize, ptr)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = if Self::over_aligned(layout) {
+            Self::alloc_over_aligned(layout, true)
+        } else {
+            libc::calloc(1, layout.size()) as *mut u8
+        };
+
+        Self::report(layout.size() as isize, ptr)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if Self::over_aligned(layout) {
+            libc::free(*(ptr as *mut *mut c_void).sub(1))
+        } else {
+            libc::free(ptr as *mut c_void)
+        }
+
+        rb_gc_adjust_memory_usage(-(layout.size() as ssize_t));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if Self::over_aligned(layout) {
+            let new_ptr = self.alloc(Layout::from_size_align_unchecked(new_size, layout.align()));
+
+            if !new_ptr.is_null() {
+                ptr.copy_to_nonoverlapping(new_ptr, layout.size().min(new_size));
+                self.dealloc(ptr, layout);
+            }
+
+            new_ptr
+        } else {
+            let new_ptr = libc::realloc(ptr as *mut c_void, new_size) as *mut u8;
+
+            Self::report(new_size as isize - layout.size() as isize, new_ptr)
+        }
+    }
+}
+
+tests! {
+    use super::*;
+    use super::super::testing::Assertions;
+    use std::slice;
+    use std::thread;
+
+    #[test]
+    fn test_ruby_allocator(assert: &mut Assertions) {
+        let allocator = RubyAllocator;
+
+        for &(size, align) in &[(8, 8), (24, 16), (3, 1), (4, 8), (100, 64), (4096, 4096)] {
+            let layout = Layout::from_size_align(size, align).unwrap();
+
+            unsafe {
+                let ptr = allocator.alloc_zeroed(layout);
+
+                assert.rs_eq_msg(0, ptr as usize % align, Some(format!("alignment of {:?}", layout)));
+                assert.rs_eq_msg(true, slice::from_raw_parts(ptr, size).iter().all(|&byte| byte == 0), Some(format!("zeroed {:?}", layout)));
+
+                ptr.write_bytes(0xff, size);
+
+                let ptr = allocator.realloc(ptr, layout, size * 2);
+
+                assert.rs_eq_msg(0, ptr as usize % align, Some(format!("alignment of reallocated {:?}", layout)));
+                assert.rs_eq_msg(true, slice::from_raw_parts(ptr, size).iter().all(|&byte| byte == 0xff), Some(format!("reallocated {:?}", layout)));
+
+                allocator.dealloc(ptr, Layout::from_size_align(size * 2, align).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_ruby_allocator_accounting(assert: &mut Assertions) {
+        let malloc_increase_bytes = unsafe { rb_id2sym(rb_intern(cstr!("malloc_increase_bytes"))) };
+        let layout = Layout::from_size_align(4096, 8).unwrap();
+
+        unsafe { rb_gc_disable() };
+
+        let before = unsafe { rb_gc_stat(malloc_increase_bytes) };
+        let ptr = unsafe { RubyAllocator.alloc(layout) };
+        let after = unsafe { rb_gc_stat(malloc_increase_bytes) };
+        unsafe { RubyAllocator.dealloc(ptr, layout) };
+
+        unsafe { rb_gc_enable() };
+
+        assert.rs_eq(true, after >= before + 4096);
+    }
+
+    #[test]
+    fn test_ruby_allocator_thread(assert: &mut Assertions) {
+        let layout = Layout::from_size_align(64, 8).unwrap();
+
+        let allocated = thread::spawn(move || unsafe {
+            let ptr = RubyAllocator.alloc(layout);
+            let allocated = !ptr.is_null();
+
+            RubyAllocator.dealloc(ptr, layout);
+
+            allocated
+        });
+
+        assert.rs_eq(true, allocated.join().unwrap());
+    }
+}
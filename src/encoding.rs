@@ -1,7 +1,25 @@
 use super::*;
-use libc::c_int;
+use libc::{c_char, c_int, c_long};
 
-extern {
+/// An encoding, as defined by Onigmo, Ruby's regular expression engine.
+///
+/// Only ever used behind a pointer: Ruby's encodings live for the rest of the
+/// process and their fields are read through functions such as
+/// [`rb_enc_name`] rather than directly.
+#[repr(C)]
+pub struct OnigEncodingType {
+    _private: [u8; 0],
+}
+
+/// A Ruby encoding, `rb_encoding*` in C.
+///
+/// Get one with [`rb_enc_from_index`], [`rb_enc_get`] or
+/// [`rb_enc_compatible`], and turn it into an [`Encoding`](rb_cEncoding)
+/// object with [`rb_enc_from_encoding`].
+#[allow(non_camel_case_types)]
+pub type rb_encoding = OnigEncodingType;
+
+extern "C" {
     /// Returns the encoding index of the provided Ruby object
     ///
     /// * `obj` - a variety of different Ruby object types are accepted
@@ -37,12 +55,290 @@ extern {
     ///     [encoding.c](https://github.com/ruby/ruby/blob/v2_5_1/encoding.c#L1339-L1343)
     /// * **2.6:** [encoding.h](https://github.com/ruby/ruby/blob/v2_6_0_preview2/include/ruby/encoding.h#L269)
     pub fn rb_utf8_encindex() -> c_int;
+
+    /// Returns the encoding index for ASCII-8BIT, i.e. binary data
+    ///
+    /// # Safety
+    ///
+    /// No known issues
+    pub fn rb_ascii8bit_encindex() -> c_int;
+
+    /// Returns the encoding index for US-ASCII
+    ///
+    /// # Safety
+    ///
+    /// No known issues
+    pub fn rb_usascii_encindex() -> c_int;
+
+    /// Returns the encoding index for the locale encoding, i.e.
+    /// `Encoding.find('locale')`
+    ///
+    /// # Safety
+    ///
+    /// No known issues
+    pub fn rb_locale_encindex() -> c_int;
+
+    /// Returns the encoding index for file names, i.e.
+    /// `Encoding.find('filesystem')`
+    ///
+    /// # Safety
+    ///
+    /// No known issues
+    pub fn rb_filesystem_encindex() -> c_int;
+
+    /// Looks up an encoding index by name
+    ///
+    /// * `name` - an encoding name or alias, case-insensitive, e.g.
+    /// `"UTF-8"` or `"binary"`, as a nul-terminated C string
+    /// * Returns the encoding index, or `-1` if there is no such encoding
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `name` is not a valid, nul-terminated C string
+    ///
+    /// # Miscellaneous
+    ///
+    /// * Loads the encoding's extension if it is not loaded yet.
+    pub fn rb_enc_find_index(name: *const c_char) -> c_int;
+
+    /// Changes the encoding of an object, without converting its contents
+    ///
+    /// This is the equivalent of `String#force_encoding`.
+    ///
+    /// * `obj` - a [`String`](rb_cString), [`Symbol`](rb_cSymbol) or
+    /// [`Regexp`](rb_cRegexp), or an object with an encoding instance
+    /// variable
+    /// * `index` - an encoding index
+    /// * Returns `obj`
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * `FrozenError`
+    ///     * if `obj` is frozen, which includes special constants such as
+    ///     `nil` or an `Integer`
+    /// * [`EncodingError`](rb_eEncodingError)
+    ///     * if `index` is not a valid encoding index
+    ///
+    /// # Miscellaneous
+    ///
+    /// * The coderange of a string is cleared when its encoding changes.
+    pub fn rb_enc_associate_index(obj: VALUE, index: c_int) -> VALUE;
+
+    /// Sets the encoding index of an object, for objects which are being
+    /// created
+    ///
+    /// Unlike [`rb_enc_associate_index`], a string's cached coderange is kept.
+    ///
+    /// * `obj` - same as [`rb_enc_associate_index`]
+    /// * `index` - an encoding index
+    ///
+    /// # Safety
+    ///
+    /// * A string's contents must be valid for its cached coderange in the
+    /// new encoding
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`EncodingError`](rb_eEncodingError)
+    ///     * if `index` is not a valid encoding index
+    /// * `FrozenError`
+    ///     * if `obj` is frozen
+    pub fn rb_enc_set_index(obj: VALUE, index: c_int);
+
+    /// Gives an object the same encoding as another one, like
+    /// [`rb_enc_associate_index`]
+    ///
+    /// * `dst` - the object whose encoding is changed
+    /// * `src` - the object whose encoding is copied
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * `FrozenError`
+    ///     * if `dst` is frozen
+    pub fn rb_enc_copy(dst: VALUE, src: VALUE);
+
+    /// Returns the encoding that the concatenation of two objects would have
+    ///
+    /// * `str1` - a [`String`](rb_cString), [`Symbol`](rb_cSymbol),
+    /// [`Regexp`](rb_cRegexp) or object with an encoding
+    /// * `str2` - same as `str1`
+    /// * Returns the resulting encoding, or null if the encodings are not
+    /// compatible
+    ///
+    /// # Safety
+    ///
+    /// No known issues
+    pub fn rb_enc_compatible(str1: VALUE, str2: VALUE) -> *const rb_encoding;
+
+    /// Returns the encoding with the given index
+    ///
+    /// * `index` - an encoding index
+    /// * Returns the encoding, or null if `index` is not a valid encoding
+    /// index
+    ///
+    /// # Safety
+    ///
+    /// No known issues
+    pub fn rb_enc_from_index(index: c_int) -> *const rb_encoding;
+
+    /// Returns the index of an encoding
+    ///
+    /// * `enc` - an encoding
+    /// * Returns the encoding index, or `0` if `enc` is null
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `enc` is not null nor a Ruby encoding
+    pub fn rb_enc_to_index(enc: *const rb_encoding) -> c_int;
+
+    /// Returns the encoding of an object, like [`rb_enc_get_index`]
+    ///
+    /// * `obj` - same as [`rb_enc_get_index`]
+    /// * Returns the encoding, or null if the encoding cannot be determined
+    ///
+    /// # Safety
+    ///
+    /// No known issues
+    pub fn rb_enc_get(obj: VALUE) -> *const rb_encoding;
+
+    /// Returns the name of an encoding, e.g. `"UTF-8"`
+    ///
+    /// * `enc` - an encoding
+    /// * Returns a nul-terminated C string, valid for the rest of the process
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `enc` is not a Ruby encoding
+    #[link_name = "RS_rb_enc_name"]
+    pub fn rb_enc_name(enc: *const rb_encoding) -> *const c_char;
+
+    /// Returns the maximum length of a character in an encoding, in bytes
+    ///
+    /// * `enc` - an encoding
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `enc` is not a Ruby encoding
+    #[link_name = "RS_rb_enc_mbmaxlen"]
+    pub fn rb_enc_mbmaxlen(enc: *const rb_encoding) -> c_int;
+
+    /// Returns the minimum length of a character in an encoding, in bytes
+    ///
+    /// Encodings with a minimum length greater than 1, such as UTF-16, are
+    /// not ASCII-compatible.
+    ///
+    /// * `enc` - an encoding
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `enc` is not a Ruby encoding
+    #[link_name = "RS_rb_enc_mbminlen"]
+    pub fn rb_enc_mbminlen(enc: *const rb_encoding) -> c_int;
+
+    /// Constructs a new Ruby string from a C string of a given length, in the
+    /// given encoding
+    ///
+    /// * `ptr` - pointer to the bytes, already in encoding `enc`
+    /// * `len` - the number of bytes
+    /// * `enc` - an encoding
+    /// * Returns a [`String`](rb_cString)
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `ptr` does not point to at least `len` bytes,
+    /// unless `ptr` is null
+    /// * Undefined behavior if `enc` is not a Ruby encoding
+    ///
+    /// # Miscellaneous
+    ///
+    /// * The bytes are not checked; invalid ones give the string a broken
+    /// coderange.
+    pub fn rb_enc_str_new(ptr: *const c_char, len: c_long, enc: *const rb_encoding) -> VALUE;
+
+    /// Constructs a new Ruby string from a nul-terminated C string, in the
+    /// given encoding
+    ///
+    /// * `ptr` - a nul-terminated C string, already in encoding `enc`
+    /// * `enc` - an encoding whose characters can be a single byte, see
+    /// [`rb_enc_mbminlen`]
+    /// * Returns a [`String`](rb_cString)
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `ptr` is not a valid, nul-terminated C string
+    /// * Undefined behavior if `enc` is not a Ruby encoding
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`ArgumentError`](rb_eArgError)
+    ///     * if `ptr` is null
+    ///     * if the minimum character length of `enc` is not 1, such as for
+    ///     UTF-16 (`"wchar encoding given"`)
+    pub fn rb_enc_str_new_cstr(ptr: *const c_char, enc: *const rb_encoding) -> VALUE;
+
+    /// Returns the [`Encoding`](rb_cEncoding) object for an encoding
+    ///
+    /// * `enc` - an encoding
+    /// * Returns an [`Encoding`](rb_cEncoding), or [`nil`](Qnil) if `enc` is
+    /// null
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `enc` is not null nor a Ruby encoding
+    pub fn rb_enc_from_encoding(enc: *const rb_encoding) -> VALUE;
+
+    /// The coderange of a string that hasn't been scanned yet.
+    #[link_name = "RS_ENC_CODERANGE_UNKNOWN"]
+    pub static ENC_CODERANGE_UNKNOWN: c_int;
+
+    /// The coderange of a string in an ASCII-compatible encoding which only
+    /// contains ASCII characters.
+    #[link_name = "RS_ENC_CODERANGE_7BIT"]
+    pub static ENC_CODERANGE_7BIT: c_int;
+
+    /// The coderange of a string which is valid in its encoding and contains
+    /// non-ASCII characters.
+    #[link_name = "RS_ENC_CODERANGE_VALID"]
+    pub static ENC_CODERANGE_VALID: c_int;
+
+    /// The coderange of a string which is not valid in its encoding.
+    #[link_name = "RS_ENC_CODERANGE_BROKEN"]
+    pub static ENC_CODERANGE_BROKEN: c_int;
+
+    /// Returns the coderange of a Ruby string, scanning it if it isn't cached
+    /// yet.
+    ///
+    /// * `string` - a [`String`](rb_cString)
+    /// * Returns [`ENC_CODERANGE_7BIT`], [`ENC_CODERANGE_VALID`] or
+    /// [`ENC_CODERANGE_BROKEN`]
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `string` is not a `String`
+    pub fn rb_enc_str_coderange(string: VALUE) -> c_int;
+
+    /// Checks whether a Ruby string is in an ASCII-compatible encoding and
+    /// only contains ASCII characters.
+    ///
+    /// * `string` - a [`String`](rb_cString)
+    /// * Returns a C boolean
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `string` is not a `String`
+    pub fn rb_enc_str_asciionly_p(string: VALUE) -> c_int;
 }
 
 tests! {
     use super::*;
-    use super::super::testing::Assertions;
+    use super::super::testing::{Assertions, ToRuby, lazy_eval};
     use libc::{c_long, c_char};
+    use std::ffi::CStr;
+    use std::ptr;
 
     #[test]
     fn test_rb_utf8_encindex(assert: &mut Assertions) {
@@ -52,4 +348,134 @@ tests! {
         let ruby_string = unsafe { rb_utf8_str_new(ptr, len) };
         assert.rs_eq(unsafe { rb_enc_get_index(ruby_string) }, unsafe { rb_utf8_encindex() });
     }
+
+    fn encoding_of(obj: VALUE) -> VALUE {
+        unsafe { rb_funcall!(obj, rb_intern(cstr!("encoding"))) }
+    }
+
+    #[test]
+    fn test_rb_encindex(assert: &mut Assertions) {
+        let binary = unsafe { rb_str_new(ptr::null(), 0) };
+        let ascii = unsafe { rb_usascii_str_new(ptr::null(), 0) };
+
+        assert.rs_eq(unsafe { rb_ascii8bit_encindex() }, unsafe { rb_enc_get_index(binary) });
+        assert.rs_eq(unsafe { rb_usascii_encindex() }, unsafe { rb_enc_get_index(ascii) });
+
+        assert.rs_eq(unsafe { rb_utf8_encindex() }, unsafe { rb_enc_find_index(cstr!("UTF-8")) });
+        assert.rs_eq(unsafe { rb_utf8_encindex() }, unsafe { rb_enc_find_index(cstr!("utf-8")) });
+        assert.rs_eq(unsafe { rb_ascii8bit_encindex() }, unsafe { rb_enc_find_index(cstr!("BINARY")) });
+        assert.rs_eq(unsafe { rb_usascii_encindex() }, unsafe { rb_enc_find_index(cstr!("ASCII")) });
+        assert.rs_eq(-1, unsafe { rb_enc_find_index(cstr!("not an encoding")) });
+
+        let locale = unsafe { rb_enc_associate_index(rb_str_new(ptr::null(), 0), rb_locale_encindex()) };
+        let filesystem = unsafe { rb_enc_associate_index(rb_str_new(ptr::null(), 0), rb_filesystem_encindex()) };
+
+        assert.rb_eq(lazy_eval("Encoding.find('locale')"), encoding_of(locale));
+        assert.rb_eq(lazy_eval("Encoding.find('filesystem')"), encoding_of(filesystem));
+    }
+
+    #[test]
+    fn test_rb_enc_associate_index(assert: &mut Assertions) {
+        let string = unsafe { rb_str_new("日本".as_ptr() as *const c_char, 6) };
+
+        assert.rb_eq(lazy_eval("Encoding::ASCII_8BIT"), encoding_of(string));
+        assert.rs_eq(string, unsafe { rb_enc_associate_index(string, rb_utf8_encindex()) });
+        assert.rb_eq(lazy_eval("Encoding::UTF_8"), encoding_of(string));
+        assert.rb_eq(lazy_eval("'日本'"), string);
+
+        let shift_jis = unsafe { rb_enc_find_index(cstr!("Shift_JIS")) };
+        let string = unsafe { rb_str_new(cstr!("ruby"), 4) };
+
+        unsafe { rb_enc_set_index(string, shift_jis) };
+
+        assert.rb_eq(lazy_eval("Encoding::Shift_JIS"), encoding_of(string));
+
+        let copy = unsafe { rb_str_new(cstr!("copy"), 4) };
+
+        unsafe { rb_enc_copy(copy, string) };
+
+        assert.rb_eq(lazy_eval("Encoding::Shift_JIS"), encoding_of(copy));
+        assert.rs_eq(shift_jis, unsafe { rb_enc_get_index(copy) });
+    }
+
+    #[test]
+    fn test_rb_enc_compatible(assert: &mut Assertions) {
+        let ascii = unsafe { rb_usascii_str_new(cstr!("ascii"), 5) };
+        let utf8 = "日本".to_ruby();
+        let binary = unsafe { rb_str_new(b"\xff".as_ptr() as *const c_char, 1) };
+
+        assert.rs_eq(false, unsafe { rb_enc_compatible(ascii, utf8) }.is_null());
+        assert.rs_eq(false, unsafe { rb_enc_compatible(ascii, binary) }.is_null());
+        assert.rs_eq(true, unsafe { rb_enc_compatible(utf8, binary) }.is_null());
+        assert.rs_eq(unsafe { rb_enc_compatible(utf8, utf8) }, unsafe { rb_enc_compatible(ascii, utf8) });
+        assert.rs_eq(unsafe { rb_enc_from_index(rb_utf8_encindex()) }, unsafe { rb_enc_compatible(ascii, utf8) });
+    }
+
+    #[test]
+    fn test_rb_enc_from_index(assert: &mut Assertions) {
+        let utf8 = unsafe { rb_enc_from_index(rb_utf8_encindex()) };
+        let utf16 = unsafe { rb_enc_from_index(rb_enc_find_index(cstr!("UTF-16LE"))) };
+
+        assert.rs_eq(false, utf8.is_null());
+        assert.rs_eq(true, unsafe { rb_enc_from_index(-1) }.is_null());
+        assert.rs_eq(unsafe { rb_utf8_encindex() }, unsafe { rb_enc_to_index(utf8) });
+        assert.rs_eq(utf8, unsafe { rb_enc_get("foo".to_ruby()) });
+
+        assert.rs_eq("UTF-8", unsafe { CStr::from_ptr(rb_enc_name(utf8)) }.to_str().unwrap());
+        assert.rs_eq("UTF-16LE", unsafe { CStr::from_ptr(rb_enc_name(utf16)) }.to_str().unwrap());
+
+        assert.rs_eq(1, unsafe { rb_enc_mbminlen(utf8) });
+        assert.rs_eq(4, unsafe { rb_enc_mbmaxlen(utf8) });
+        assert.rs_eq(2, unsafe { rb_enc_mbminlen(utf16) });
+        assert.rs_eq(4, unsafe { rb_enc_mbmaxlen(utf16) });
+
+        assert.rb_eq(lazy_eval("Encoding::UTF_8"), unsafe { rb_enc_from_encoding(utf8) });
+        assert.rb_eq(lazy_eval("Encoding::UTF_16LE"), unsafe { rb_enc_from_encoding(utf16) });
+        assert.rb_nil(unsafe { rb_enc_from_encoding(ptr::null()) });
+    }
+
+    #[test]
+    fn test_rb_enc_str_new(assert: &mut Assertions) {
+        let shift_jis = unsafe { rb_enc_from_index(rb_enc_find_index(cstr!("Shift_JIS"))) };
+        let utf16 = unsafe { rb_enc_from_index(rb_enc_find_index(cstr!("UTF-16LE"))) };
+
+        let bytes = b"\x93\xfa\x96\x7b";
+        let string = unsafe { rb_enc_str_new(bytes.as_ptr() as *const c_char, bytes.len() as c_long, shift_jis) };
+
+        assert.rb_eq(lazy_eval("'日本'.encode('Shift_JIS')"), string);
+        assert.rs_eq(shift_jis, unsafe { rb_enc_get(string) });
+
+        let bytes = b"r\0u\0b\0y\0";
+        let string = unsafe { rb_enc_str_new(bytes.as_ptr() as *const c_char, bytes.len() as c_long, utf16) };
+
+        assert.rb_eq(lazy_eval("'ruby'.encode('UTF-16LE')"), string);
+
+        let string = unsafe { rb_enc_str_new_cstr(cstr!("ascii"), shift_jis) };
+
+        assert.rb_eq(lazy_eval("'ascii'.encode('Shift_JIS')"), string);
+        assert.rb_eq(lazy_eval("Encoding::Shift_JIS"), encoding_of(string));
+
+        extern "C" fn __test_enc_str_new_cstr_wchar__(_self: VALUE) -> VALUE {
+            unsafe { rb_enc_str_new_cstr(cstr!("ruby"), rb_enc_from_index(rb_enc_find_index(cstr!("UTF-16LE")))) }
+        }
+
+        unsafe {
+            define_method(
+                rb_mKernel,
+                cstr!("__test_enc_str_new_cstr_wchar__"),
+                METHOD_FUNC::arity_0(__test_enc_str_new_cstr_wchar__)
+            );
+        }
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_enc_str_new_cstr_wchar__
+                rescue Exception => e
+                [e.class, e.message].inspect
+                end
+            "#),
+            r#"[ArgumentError, "wchar encoding given"]"#.to_ruby()
+        );
+    }
 }
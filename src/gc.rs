@@ -0,0 +1,284 @@
+use super::*;
+use std::marker::PhantomData;
+use std::ptr;
+
+/// A `VALUE` registered as a GC root for as long as the handle lives.
+///
+/// The `VALUE` is kept on the heap so that its address stays the same when
+/// the handle is moved. It is registered with [`rb_gc_register_address`] on
+/// creation and unregistered with [`rb_gc_unregister_address`] on drop.
+///
+/// This is useful for keeping objects alive from places the GC doesn't scan,
+/// such as Rust collections or other heap-allocated structs.
+///
+/// Handles are neither `Send` nor `Sync`, as the GC may only be called into
+/// from a Ruby thread.
+#[derive(Debug)]
+pub struct Rooted {
+    value: Box<VALUE>,
+    _marker: PhantomData<*mut VALUE>,
+}
+
+impl Rooted {
+    /// Roots `value` until the handle is dropped.
+    ///
+    /// # Safety
+    ///
+    /// * Must be called (and dropped) on a Ruby thread
+    pub unsafe fn new(value: VALUE) -> Self {
+        let mut value = Box::new(value);
+        rb_gc_register_address(&mut *value);
+
+        Rooted { value, _marker: PhantomData }
+    }
+
+    /// Returns the rooted `VALUE`.
+    pub fn get(&self) -> VALUE {
+        *self.value
+    }
+
+    /// Replaces the rooted `VALUE`, letting the previous one be collected.
+    pub fn set(&mut self, value: VALUE) {
+        *self.value = value;
+    }
+}
+
+impl Drop for Rooted {
+    fn drop(&mut self) {
+        unsafe { rb_gc_unregister_address(&mut *self.value) }
+    }
+}
+
+/// A `VALUE` cached for the rest of the process, e.g. in a `lazy_static`.
+///
+/// The `VALUE` is leaked on the heap and registered with
+/// [`rb_gc_register_address`], so the GC keeps the object alive. Registered
+/// addresses are marked with [`rb_gc_mark_maybe`], which pins the object, so
+/// `GC.compact` never moves it and the cached `VALUE` stays valid.
+///
+/// ```ignore
+/// lazy_static! {
+///     static ref FOO: GlobalValue = unsafe {
+///         GlobalValue::new(rb_const_get(rb_cObject, rb_intern(cstr!("Foo"))))
+///     };
+/// }
+///
+/// let foo = FOO.get();
+/// ```
+#[derive(Debug)]
+pub struct GlobalValue {
+    value: *mut VALUE,
+}
+
+// The slot is immutable after `new`: the object is pinned, so nothing ever
+// writes the `VALUE` again and it can be read from any thread.
+unsafe impl Send for GlobalValue {}
+unsafe impl Sync for GlobalValue {}
+
+impl GlobalValue {
+    /// Caches `value`, keeping it alive for the rest of the process.
+    ///
+    /// # Safety
+    ///
+    /// * Must be called on a Ruby thread
+    pub unsafe fn new(value: VALUE) -> Self {
+        let value = Box::into_raw(Box::new(value));
+        rb_gc_register_address(value);
+
+        GlobalValue { value }
+    }
+
+    /// Returns the cached `VALUE`.
+    pub fn get(&self) -> VALUE {
+        unsafe { *self.value }
+    }
+}
+
+/// Updates a `VALUE` to the new location of its object after `GC.compact`,
+/// from the `dcompact` function of an [`rb_data_type_t`].
+///
+/// Only needed for `VALUE`s marked with [`rb_gc_mark_movable`]; objects marked
+/// with [`rb_gc_mark`] are pinned and never move.
+///
+/// Only available with the `ruby_2_7` feature.
+///
+/// # Safety
+///
+/// * Must only be called while the GC is compacting, i.e. from a `dcompact`
+/// function
+#[cfg(feature = "ruby_2_7")]
+pub unsafe fn gc_update_location(value: &mut VALUE) {
+    *value = rb_gc_location(*value);
+}
+
+/// Keeps `value` alive on the stack up to this point, like `RB_GC_GUARD`.
+///
+/// The GC finds objects referenced from native code by scanning the stack,
+/// but once a `VALUE` is no longer used, the compiler is free to discard it.
+/// When only a pointer into the object is still in use (e.g. from
+/// [`RSTRING_PTR`]), the object could then be collected while the pointer is
+/// being read. Calling this after the last use of the pointer prevents that:
+///
+/// ```ignore
+/// let string = rb_utf8_str_new(cstr!("foo"), 3);
+/// let ptr = RSTRING_PTR(string);
+///
+/// // ... code that may allocate, reading from `ptr` ...
+///
+/// gc_guard(&string);
+/// ```
+///
+/// # Ruby Documentation
+///
+/// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Appendix+E.+RB_GC_GUARD+to+protect+from+premature+GC)
+#[inline(never)]
+pub fn gc_guard(value: &VALUE) {
+    unsafe { ptr::read_volatile(value) };
+}
+
+tests! {
+    use super::*;
+    use super::super::testing::{Assertions, ToRuby, lazy_eval};
+    use std::slice;
+
+    #[inline(never)]
+    fn new_string(s: &str) -> VALUE {
+        s.to_ruby()
+    }
+
+    fn gc_start() {
+        unsafe { rb_gc_start() };
+    }
+
+    #[test]
+    fn test_rooted(assert: &mut Assertions) {
+        let mut rooted = unsafe { Rooted::new(new_string("foo")) };
+        let moved = vec![unsafe { Rooted::new(new_string("bar")) }];
+
+        gc_start();
+
+        assert.rb_eq("foo".to_ruby(), rooted.get());
+        assert.rb_eq("bar".to_ruby(), moved[0].get());
+
+        rooted.set(new_string("baz"));
+        drop(moved);
+
+        gc_start();
+
+        assert.rb_eq("baz".to_ruby(), rooted.get());
+    }
+
+    #[test]
+    fn test_gc_register_mark_object(assert: &mut Assertions) {
+        let value = new_string("foo");
+
+        unsafe { rb_gc_register_mark_object(value) };
+
+        gc_start();
+
+        assert.rb_eq(lazy_eval("'foo'"), value);
+    }
+
+    // A smoke test that `gc_guard` compiles and is harmless to call: the
+    // conservative stack scan may keep `string` alive on its own, so this
+    // can't detect a `gc_guard` that does nothing.
+    #[test]
+    fn test_gc_guard_smoke(assert: &mut Assertions) {
+        let string = new_string("a string that is long enough to live outside of its object");
+        let ptr = unsafe { RSTRING_PTR(string) };
+        let len = unsafe { RSTRING_LEN(string) };
+
+        unsafe { rb_funcall!(rb_mGC, rb_intern(cstr!("stress=")), Qtrue) };
+
+        for i in 0..16 {
+            new_string(&format!("garbage {}", i));
+        }
+
+        gc_start();
+
+        let bytes = unsafe { slice::from_raw_parts(ptr as *const u8, len as usize) }.to_vec();
+
+        gc_guard(&string);
+
+        unsafe { rb_funcall!(rb_mGC, rb_intern(cstr!("stress=")), Qfalse) };
+
+        assert.rs_eq(&b"a string that is long enough to live outside of its object"[..], &bytes[..]);
+    }
+
+    #[test]
+    fn test_global_value(assert: &mut Assertions) {
+        let global = unsafe { GlobalValue::new(new_string("foo")) };
+
+        gc_start();
+
+        #[cfg(feature = "ruby_2_7")]
+        gc_compact();
+
+        assert.rb_eq(lazy_eval("'foo'"), global.get());
+    }
+
+    #[cfg(feature = "ruby_2_7")]
+    fn gc_compact() {
+        unsafe {
+            let compact = rb_id2sym(rb_intern(cstr!("compact")));
+
+            // `GC.compact` is not implemented on all platforms
+            if rb_funcall!(rb_mGC, rb_intern(cstr!("respond_to?")), compact) == Qtrue {
+                rb_funcall!(rb_mGC, rb_intern(cstr!("compact")));
+            }
+        }
+    }
+
+    #[test]
+    fn test_gc_location(assert: &mut Assertions) {
+        #[cfg(feature = "ruby_2_7")]
+        {
+            use libc::c_void;
+            use std::ptr::{null, null_mut};
+
+            extern "C" fn __test_gc_location_mark__(data: *mut c_void) {
+                let value = unsafe { &*(data as *const VALUE) };
+                unsafe { rb_gc_mark_movable(*value) };
+            }
+
+            extern "C" fn __test_gc_location_compact__(data: *mut c_void) {
+                let value = unsafe { &mut *(data as *mut VALUE) };
+                unsafe { gc_update_location(value) };
+            }
+
+            extern "C" fn __test_gc_location_free__(data: *mut c_void) {
+                drop(unsafe { Box::from_raw(data as *mut VALUE) });
+            }
+
+            static GC_LOCATION_TYPE: rb_data_type_t = rb_data_type_t {
+                wrap_struct_name: cstr!("TestGcLocation"),
+                function: rb_data_type_function_t {
+                    dmark: Some(__test_gc_location_mark__),
+                    dfree: Some(__test_gc_location_free__),
+                    dsize: None,
+                    dcompact: Some(__test_gc_location_compact__),
+                    reserved: [null_mut()],
+                },
+                parent: null(),
+                data: null_mut(),
+                flags: RUBY_TYPED_FREE_IMMEDIATELY,
+            };
+
+            assert.rb_eq(unsafe { Qnil }, unsafe { rb_gc_location(Qnil) });
+            assert.rb_eq(unsafe { INT2NUM(42) }, unsafe { rb_gc_location(INT2NUM(42)) });
+
+            let value = Box::into_raw(Box::new(new_string("foo")));
+            let obj = unsafe { rb_data_typed_object_wrap(rb_cObject, value as *mut c_void, &GC_LOCATION_TYPE) };
+
+            gc_compact();
+
+            let value = unsafe { *(RTYPEDDATA_DATA(obj) as *const VALUE) };
+
+            assert.rb_eq(lazy_eval("'foo'"), value);
+            gc_guard(&obj);
+        }
+
+        #[cfg(not(feature = "ruby_2_7"))]
+        let _ = assert;
+    }
+}
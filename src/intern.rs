@@ -1,7 +1,9 @@
 use super::*;
-use libc::{c_char, c_int, c_long};
+use libc::{c_char, c_int, c_long, size_t, ssize_t};
+use std::any::Any;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
 
-extern {
+extern "C" {
     /// Constructs a new, empty array.
     ///
     /// * Returns an [`Array`](rb_cArray)
@@ -138,6 +140,8 @@ extern {
     ///         * [`ST_STOP`]: iteration will stop
     /// * `farg` - a Ruby object to be passed through to the `func`
     ///
+    /// [`hash_foreach`](fn.hash_foreach.html) takes a Rust closure instead.
+    ///
     /// # Safety
     ///
     /// * Undefined behavior if `hash` is not a `Hash`
@@ -169,6 +173,287 @@ extern {
     //+ c-func: string.c `VALUE rb_utf8_str_new(const char*, long)`
     pub fn rb_utf8_str_new(ptr: *const c_char, len: c_long) -> VALUE;
 
+    /// Constructs a new binary (`ASCII-8BIT`) Ruby string from a C string of
+    /// a given length.
+    ///
+    /// * `ptr` - pointer to the bytes, which may include nul-bytes
+    /// * `len` - the number of bytes
+    /// * Returns a [`String`](rb_cString)
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `ptr` does not point to at least `len` bytes,
+    /// unless `ptr` is null
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-String+Functions)
+    ///
+    //+ c-func: string.c `VALUE rb_str_new(const char*, long)`
+    pub fn rb_str_new(ptr: *const c_char, len: c_long) -> VALUE;
+
+    /// Constructs a new binary (`ASCII-8BIT`) Ruby string from a nul-terminated
+    /// C string.
+    ///
+    /// * `ptr` - a nul-terminated C string
+    /// * Returns a [`String`](rb_cString)
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `ptr` is not a valid, nul-terminated C string
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`ArgumentError`](rb_eArgError)
+    ///     * if `ptr` is null
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-String+Functions)
+    ///
+    //+ c-func: string.c `VALUE rb_str_new_cstr(const char*)`
+    pub fn rb_str_new_cstr(ptr: *const c_char) -> VALUE;
+
+    /// Constructs a new, empty binary (`ASCII-8BIT`) Ruby string, with room
+    /// for at least `capa` bytes.
+    ///
+    /// Useful for building a string piece by piece, e.g. with [`rb_str_cat`].
+    ///
+    /// * `capa` - the initial capacity, in bytes
+    /// * Returns an empty [`String`](rb_cString)
+    ///
+    /// # Safety
+    ///
+    /// No known issues.
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-String+Functions)
+    ///
+    //+ c-func: string.c `VALUE rb_str_buf_new(long)`
+    pub fn rb_str_buf_new(capa: c_long) -> VALUE;
+
+    /// Appends bytes to a Ruby string, in place.
+    ///
+    /// The bytes are assumed to be in the encoding of `string`.
+    ///
+    /// * `string` - a [`String`](rb_cString)
+    /// * `ptr` - pointer to the bytes, which may include nul-bytes
+    /// * `len` - the number of bytes
+    /// * Returns `string`
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `string` is not a `String`
+    /// * Undefined behavior if `ptr` does not point to at least `len` bytes
+    ///
+    /// ## Exceptions
+    ///
+    /// * `FrozenError`
+    ///     * if `string` is frozen
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-String+Functions)
+    ///
+    //+ c-func: string.c `VALUE rb_str_cat(VALUE, const char*, long)`
+    pub fn rb_str_cat(string: VALUE, ptr: *const c_char, len: c_long) -> VALUE;
+
+    /// Appends a Ruby string to another, in place.
+    ///
+    /// This is the equivalent of `String#<<` with a `String` argument.
+    ///
+    /// * `string` - a [`String`](rb_cString)
+    /// * `other` - a [`String`](rb_cString)
+    /// * Returns `string`
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `string` or `other` is not a `String`
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`Encoding::CompatibilityError`](rb_eEncCompatError)
+    ///     * if the encodings of `string` and `other` are not compatible
+    /// * `FrozenError`
+    ///     * if `string` is frozen
+    ///
+    //+ c-func: string.c `VALUE rb_str_append(VALUE, VALUE)`
+    pub fn rb_str_append(string: VALUE, other: VALUE) -> VALUE;
+
+    /// Appends a Ruby string or a codepoint to a Ruby string, in place.
+    ///
+    /// This is the equivalent of `String#<<`.
+    ///
+    /// * `string` - a [`String`](rb_cString)
+    /// * `other` - a [`String`](rb_cString), or an [`Integer`](rb_cInteger)
+    /// codepoint
+    /// * Returns `string`
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `string` is not a `String`
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`Encoding::CompatibilityError`](rb_eEncCompatError)
+    ///     * if the encodings of `string` and `other` are not compatible
+    /// * [`RangeError`](rb_eRangeError)
+    ///     * if `other` is not a valid codepoint in the encoding of `string`
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if `other` is neither a `String` nor an `Integer`
+    /// * `FrozenError`
+    ///     * if `string` is frozen
+    ///
+    //+ c-func: string.c `VALUE rb_str_concat(VALUE, VALUE)`
+    pub fn rb_str_concat(string: VALUE, other: VALUE) -> VALUE;
+
+    /// Constructs a new `US-ASCII` Ruby string from a C string of a given
+    /// length.
+    ///
+    /// * `ptr` - pointer to the bytes
+    /// * `len` - the number of bytes
+    /// * Returns a [`String`](rb_cString)
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `ptr` does not point to at least `len` bytes,
+    /// unless `ptr` is null
+    ///
+    /// # Miscellaneous
+    ///
+    /// * The bytes are not checked; a string containing non-ASCII bytes has a
+    /// broken coderange.
+    ///
+    //+ c-func: string.c `VALUE rb_usascii_str_new(const char*, long)`
+    pub fn rb_usascii_str_new(ptr: *const c_char, len: c_long) -> VALUE;
+
+    /// Constructs a new Ruby string from external data, e.g. read from a file
+    /// or the environment, tagged with `Encoding.default_external`.
+    ///
+    /// * `ptr` - pointer to the bytes
+    /// * `len` - the number of bytes
+    /// * Returns a [`String`](rb_cString)
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `ptr` does not point to at least `len` bytes,
+    /// unless `ptr` is null
+    ///
+    /// # Miscellaneous
+    ///
+    /// * When `Encoding.default_internal` is set, the string is transcoded to
+    /// it.
+    ///
+    //+ c-func: string.c `VALUE rb_external_str_new(const char*, long)`
+    pub fn rb_external_str_new(ptr: *const c_char, len: c_long) -> VALUE;
+
+    /// Returns a frozen copy of a Ruby string, sharing its contents where
+    /// possible, or the string itself if it is already frozen.
+    ///
+    /// * `string` - a [`String`](rb_cString)
+    /// * Returns a frozen [`String`](rb_cString)
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `string` is not a `String`
+    ///
+    //+ c-func: string.c `VALUE rb_str_new_frozen(VALUE)`
+    pub fn rb_str_new_frozen(string: VALUE) -> VALUE;
+
+    /// Prepares a Ruby string for in-place modification, through
+    /// [`RSTRING_PTR_MUT`].
+    ///
+    /// If the string shares its buffer with other strings, it gets its own
+    /// copy. Its cached coderange is also cleared, as the contents are about
+    /// to change.
+    ///
+    /// * `string` - a [`String`](rb_cString)
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `string` is not a `String`
+    ///
+    /// ## Exceptions
+    ///
+    /// * `FrozenError`
+    ///     * if `string` is frozen
+    ///
+    //+ c-func: string.c `void rb_str_modify(VALUE)`
+    pub fn rb_str_modify(string: VALUE);
+
+    /// Like [`rb_str_modify`], but also ensures that the string has room for
+    /// at least `expand` more bytes after its current length.
+    ///
+    /// * `string` - a [`String`](rb_cString)
+    /// * `expand` - the number of bytes to reserve
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `string` is not a `String`
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`ArgumentError`](rb_eArgError)
+    ///     * if `expand` is negative or the new size overflows
+    /// * `FrozenError`
+    ///     * if `string` is frozen
+    ///
+    //+ c-func: string.c `void rb_str_modify_expand(VALUE, long)`
+    pub fn rb_str_modify_expand(string: VALUE, expand: c_long);
+
+    /// Resizes a Ruby string to the given byte length, in place.
+    ///
+    /// The string is truncated, or extended with unspecified bytes.
+    ///
+    /// * `string` - a [`String`](rb_cString)
+    /// * `len` - the new length, in bytes
+    /// * Returns `string`
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `string` is not a `String`
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`ArgumentError`](rb_eArgError)
+    ///     * if `len` is negative
+    /// * `FrozenError`
+    ///     * if `string` is frozen
+    ///
+    //+ c-func: string.c `VALUE rb_str_resize(VALUE, long)`
+    pub fn rb_str_resize(string: VALUE, len: c_long) -> VALUE;
+
+    /// Sets the byte length of a Ruby string, after its contents have been
+    /// written through [`RSTRING_PTR_MUT`].
+    ///
+    /// Unlike [`rb_str_resize`], the buffer is never reallocated.
+    ///
+    /// * `string` - a [`String`](rb_cString)
+    /// * `len` - the new length, in bytes
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `string` is not a `String`
+    /// * `string` must have been prepared with [`rb_str_modify`] or
+    /// [`rb_str_modify_expand`]
+    /// * The first `len` bytes must have been initialized
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`RuntimeError`](rb_eRuntimeError)
+    ///     * if `string` shares its buffer with other strings
+    /// * `FrozenError`
+    ///     * if `string` is frozen
+    ///
+    /// # Miscellaneous
+    ///
+    /// * Aborts the process if `len` is negative or greater than the capacity
+    /// of `string`.
+    ///
+    //+ c-func: string.c `void rb_str_set_len(VALUE, long)`
+    pub fn rb_str_set_len(string: VALUE, len: c_long);
+
     /// Constructs a new instance of a class by calling its allocator and constructor
     /// (`alloc` and `initialize`) as `::new` normally would.
     ///
@@ -193,6 +478,99 @@ extern {
     //+ c-func: object.c `VALUE rb_class_new_instance(int, const VALUE*, VALUE)`
     pub fn rb_class_new_instance(argc: c_int, argv: *const VALUE, class: VALUE) -> VALUE;
 
+    /// Allocates a new instance of a class, without calling `initialize`.
+    ///
+    /// This is the equivalent of `Class#allocate`, and calls the allocator
+    /// function set with [`rb_define_alloc_func`].
+    ///
+    /// * `class` - a [`Class`](rb_cClass)
+    /// * Returns a new, uninitialized instance of `class`
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if `class` is not a class, is a singleton class, or its allocator
+    ///     is undefined
+    ///
+    //+ c-func: object.c `VALUE rb_obj_alloc(VALUE)`
+    pub fn rb_obj_alloc(class: VALUE) -> VALUE;
+
+    /// Calls `initialize` on an object, passing it the given arguments and
+    /// the block of the current method, if any.
+    ///
+    /// Together with [`rb_obj_alloc`], this is the equivalent of
+    /// [`rb_class_new_instance`].
+    ///
+    /// * `obj` - any Ruby object
+    /// * `argc` - the number of arguments
+    /// * `argv` - pointer to the arguments, passed as a C array
+    ///
+    /// # Safety
+    ///
+    /// * `argv` must point to a location in memory containing at least `argc` number
+    /// of Ruby objects, (i.e. a valid C `VALUE` array of at least size `argc`)
+    ///
+    /// ## Exceptions
+    ///
+    /// * Any exception raised by `initialize`
+    ///
+    //+ c-func: eval.c `void rb_obj_call_init(VALUE, int, const VALUE*)`
+    pub fn rb_obj_call_init(obj: VALUE, argc: c_int, argv: *const VALUE);
+
+    /// The default implementation of `initialize_copy`, checking that a copy
+    /// can be made from `orig`.
+    ///
+    /// Should be called first by native `initialize_copy` methods, which are
+    /// invoked by `dup` and `clone` on an object freshly allocated with the
+    /// allocator function of `orig`'s class.
+    ///
+    /// * `obj` - the copy
+    /// * `orig` - the object being copied
+    /// * Returns `obj`
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if `orig` is not an instance of the same class as `obj`
+    /// * `FrozenError`
+    ///     * if `obj` is frozen
+    ///
+    //+ c-func: object.c `VALUE rb_obj_init_copy(VALUE,VALUE)`
+    pub fn rb_obj_init_copy(obj: VALUE, orig: VALUE) -> VALUE;
+
+    /// Sets the allocator function of a class, which is called by
+    /// `Class#allocate`, `Class#new`, `dup` and `clone` to create its instances.
+    ///
+    /// Classes backed by native data should define one, so that instances
+    /// always wrap the expected data type.
+    ///
+    /// * `class` - a [`Class`](rb_cClass)
+    /// * `func` - the allocator function
+    ///
+    /// # Safety
+    ///
+    /// * `class` must be a `Class`
+    ///
+    //+ c-func: vm_method.c `void rb_define_alloc_func(VALUE, rb_alloc_func_t)`
+    pub fn rb_define_alloc_func(class: VALUE, func: rb_alloc_func_t);
+
+    /// Removes the allocator function of a class, so that it can no longer be
+    /// instantiated. This is the equivalent of `undef_method :allocate` on its
+    /// singleton class.
+    ///
+    /// * `class` - a [`Class`](rb_cClass)
+    ///
+    /// # Safety
+    ///
+    /// * `class` must be a `Class`
+    ///
+    //+ c-func: vm_method.c `void rb_undef_alloc_func(VALUE)`
+    pub fn rb_undef_alloc_func(class: VALUE);
+
     /// Fetches a constant from a module or class.
     ///
     /// * `class` - a [`Class`](rb_cClass) or [`Module`](rb_cModule)
@@ -278,31 +656,569 @@ extern {
     ///
     //+ c-func: class.c `void rb_define_singleton_method(VALUE, const char*, VALUE(*)(ANYARGS), int)`
     pub fn rb_define_singleton_method(class: VALUE, name: *const c_char, func: ANYARGS<VALUE>, arity: c_int);
-}
-
-tests! {
-    use super::*;
-    use super::super::testing::{Assertions, ToRuby, lazy_eval};
-    use std::ptr::null;
 
-    #[test]
-    fn test_ary_new(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("[]"), unsafe { rb_ary_new() });
+    /// Defines a private method on a class.
+    ///
+    /// See [`rb_define_method`](rb_define_method) for details on arguments,
+    /// and [`define_private_method`] for a typed equivalent.
+    ///
+    /// # Safety
+    ///
+    /// See [`rb_define_method`](fn.rb_define_method.html#safety).
+    ///
+    //+ c-func: class.c `void rb_define_private_method(VALUE, const char*, VALUE (*)(ANYARGS), int)`
+    pub fn rb_define_private_method(class: VALUE, name: *const c_char, func: ANYARGS<VALUE>, arity: c_int);
 
-        let arr1 = unsafe { rb_ary_new() };
+    /// Defines a protected method on a class.
+    ///
+    /// See [`rb_define_method`](rb_define_method) for details on arguments,
+    /// and [`define_protected_method`] for a typed equivalent.
+    ///
+    /// # Safety
+    ///
+    /// See [`rb_define_method`](fn.rb_define_method.html#safety).
+    ///
+    //+ c-func: class.c `void rb_define_protected_method(VALUE, const char*, VALUE (*)(ANYARGS), int)`
+    pub fn rb_define_protected_method(class: VALUE, name: *const c_char, func: ANYARGS<VALUE>, arity: c_int);
 
-        unsafe { rb_ary_push(arr1, Qtrue) };
-        unsafe { rb_ary_push(arr1, Qfalse) };
-        unsafe { rb_ary_push(arr1, Qnil) };
+    /// Defines a public method on a class, with its name given as an `ID`.
+    ///
+    /// See [`rb_define_method`](rb_define_method) for details on the other
+    /// arguments, and [`define_method_id`] for a typed equivalent.
+    ///
+    /// * `mid` - the `ID` of the method name
+    ///
+    /// # Safety
+    ///
+    /// See [`rb_define_method`](fn.rb_define_method.html#safety).
+    ///
+    //+ c-func: class.c `void rb_define_method_id(VALUE, ID, VALUE (*)(ANYARGS), int)`
+    pub fn rb_define_method_id(class: VALUE, mid: ID, func: ANYARGS<VALUE>, arity: c_int);
 
-        assert.rb_eq(lazy_eval("[true, false, nil]"), arr1);
+    /// Defines an alias for a method on a class, with the names given as `ID`s.
+    ///
+    /// This is the equivalent of `alias_method`; see also [`rb_define_alias`].
+    ///
+    /// * `class` - a [`Class`](rb_cClass) or [`Module`](rb_cModule)
+    /// * `alias_name` - the `ID` of the alias
+    /// * `original_name` - the `ID` of the existing method
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`NameError`](rb_eNameError)
+    ///     * if the method `original_name` is not defined
+    ///
+    //+ c-func: vm_method.c `void rb_alias(VALUE, ID, ID)`
+    pub fn rb_alias(class: VALUE, alias_name: ID, original_name: ID);
 
-        let arr2 = unsafe { rb_ary_new() };
+    /// Removes a method defined on a class, so that lookup continues with its
+    /// ancestors.
+    ///
+    /// This is the equivalent of `remove_method`, as opposed to
+    /// [`rb_undef_method`] which prevents lookup entirely.
+    ///
+    /// * `class` - a [`Class`](rb_cClass) or [`Module`](rb_cModule)
+    /// * `name` - an ASCII-encoded, nul-terminated C string
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `name` string is not valid ASCII
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`NameError`](rb_eNameError)
+    ///     * if the method is not defined on `class` itself
+    ///
+    //+ c-func: vm_method.c `void rb_remove_method(VALUE, const char*)`
+    pub fn rb_remove_method(class: VALUE, name: *const c_char);
 
-        unsafe { rb_ary_push(arr2, "hello".to_ruby()) };
-        unsafe { rb_ary_push(arr2, "world!".to_ruby()) };
+    /// Creates a new anonymous class.
+    ///
+    /// This is the equivalent of `Class.new(superclass)`, except that the
+    /// `inherited` hook is not called.
+    ///
+    /// * `superclass` - [`Class`](rb_cClass)
+    /// * Returns a [`Class`](rb_cClass) without a name
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if `superclass` is not a class, is a singleton class, or is `Class`
+    ///
+    //+ c-func: class.c `VALUE rb_class_new(VALUE)`
+    pub fn rb_class_new(superclass: VALUE) -> VALUE;
 
-        assert.rb_eq(lazy_eval("['hello', 'world!']"), arr2);
+    /// Creates a new anonymous module.
+    ///
+    /// This is the equivalent of `Module.new`.
+    ///
+    /// * Returns a [`Module`](rb_cModule) without a name
+    ///
+    /// # Safety
+    ///
+    /// No known issues.
+    ///
+    //+ c-func: class.c `VALUE rb_module_new(void)`
+    pub fn rb_module_new() -> VALUE;
+
+    /// Defines a new class under a namespace, like [`rb_define_class_under`],
+    /// with its name given as an `ID`.
+    ///
+    /// * `outer` - the namespace, a [`Class`](rb_cClass) or [`Module`](rb_cModule)
+    /// * `id` - the `ID` of the class name
+    /// * `superclass` - [`Class`](rb_cClass)
+    /// * Returns a [`Class`](rb_cClass)
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * See [`rb_define_class`](fn.rb_define_class.html#exceptions)
+    ///
+    //+ c-func: class.c `VALUE rb_define_class_id_under(VALUE, ID, VALUE)`
+    pub fn rb_define_class_id_under(outer: VALUE, id: ID, superclass: VALUE) -> VALUE;
+
+    /// Returns the singleton class of an object, creating it if needed.
+    ///
+    /// This is the equivalent of `singleton_class`.
+    ///
+    /// * `obj` - any Ruby object
+    /// * Returns a [`Class`](rb_cClass)
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if `obj` can't have a singleton class (e.g. an `Integer` or a
+    ///     `Symbol`)
+    ///
+    //+ c-func: class.c `VALUE rb_singleton_class(VALUE)`
+    pub fn rb_singleton_class(obj: VALUE) -> VALUE;
+
+    /// Checks whether a module is included in (or prepended to) a class or
+    /// module, or any of its ancestors.
+    ///
+    /// This is the equivalent of `Module#include?`.
+    ///
+    /// * `class` - a [`Class`](rb_cClass) or [`Module`](rb_cModule)
+    /// * `module` - a [`Module`](rb_cModule)
+    /// * Returns [`true`](Qtrue) or [`false`](Qfalse)
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if `module` is not a module
+    ///
+    //+ c-func: class.c `VALUE rb_mod_include_p(VALUE, VALUE)`
+    pub fn rb_mod_include_p(class: VALUE, module: VALUE) -> VALUE;
+
+    /// Returns the ancestors of a class or module, in method lookup order.
+    ///
+    /// This is the equivalent of `Module#ancestors`.
+    ///
+    /// * `class` - a [`Class`](rb_cClass) or [`Module`](rb_cModule)
+    /// * Returns an [`Array`](rb_cArray) of classes and modules
+    ///
+    /// # Safety
+    ///
+    /// No known issues.
+    ///
+    //+ c-func: class.c `VALUE rb_mod_ancestors(VALUE)`
+    pub fn rb_mod_ancestors(class: VALUE) -> VALUE;
+
+    /// Raises the given exception object.
+    ///
+    /// This function never returns; control is transferred to the nearest
+    /// `rescue` (or equivalent) on the Ruby side.
+    ///
+    /// * `exception` - an instance of [`Exception`](rb_eException) or one of its subclasses
+    ///
+    /// # Safety
+    ///
+    /// * The call unwinds via `longjmp`, so destructors of any Rust values
+    /// still on the stack will not run. Drop them before raising.
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if `exception` is not an exception object
+    ///
+    //+ c-func: eval.c `void rb_exc_raise(VALUE)`
+    pub fn rb_exc_raise(exception: VALUE) -> !;
+
+    /// Constructs a new exception of the given class with a message of the given length.
+    ///
+    /// * `class` - an exception class, such as [`rb_eArgError`]
+    /// * `ptr` - the message, as a C string
+    /// * `len` - the byte length of the message
+    /// * Returns an instance of `class`
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must point to a C string of length greater than or equal to `len`.
+    ///
+    /// ## Exceptions
+    ///
+    /// * May call user-defined code (`class.new`) that could raise an exception
+    ///
+    //+ c-func: error.c `VALUE rb_exc_new(VALUE, const char*, long)`
+    pub fn rb_exc_new(class: VALUE, ptr: *const c_char, len: c_long) -> VALUE;
+
+    /// Constructs a new exception of the given class with a nul-terminated message.
+    ///
+    /// * `class` - an exception class, such as [`rb_eArgError`]
+    /// * `cstr` - the message, as a nul-terminated C string
+    /// * Returns an instance of `class`
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `cstr` is not nul-terminated.
+    ///
+    /// ## Exceptions
+    ///
+    /// * May call user-defined code (`class.new`) that could raise an exception
+    ///
+    //+ c-func: error.c `VALUE rb_exc_new_cstr(VALUE, const char*)`
+    pub fn rb_exc_new_cstr(class: VALUE, cstr: *const c_char) -> VALUE;
+
+    /// Constructs a new exception of the given class with a Ruby string as the message.
+    ///
+    /// * `class` - an exception class, such as [`rb_eArgError`]
+    /// * `message` - a [`String`](rb_cString)
+    /// * Returns an instance of `class`
+    ///
+    /// # Safety
+    ///
+    /// * No known issues
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if `message` is not a `String`
+    /// * May call user-defined code (`class.new`) that could raise an exception
+    ///
+    //+ c-func: error.c `VALUE rb_exc_new_str(VALUE, VALUE)`
+    pub fn rb_exc_new_str(class: VALUE, message: VALUE) -> VALUE;
+
+    /// Returns the block given to the current method as a [`Proc`](rb_cProc).
+    ///
+    /// This is the equivalent of a `&block` parameter in Ruby.
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`ArgumentError`](rb_eArgError)
+    ///     * if no block was given (check with [`rb_block_given_p`] first)
+    ///
+    //+ c-func: proc.c `VALUE rb_block_proc(void)`
+    pub fn rb_block_proc() -> VALUE;
+
+    /// Marks an object as in use, from the `dmark` function of an
+    /// [`rb_data_type_t`].
+    ///
+    /// * `obj` - any Ruby object, including immediates like [`nil`](Qnil)
+    ///
+    /// # Safety
+    ///
+    /// * Must only be called while the GC is marking, i.e. from a `dmark`
+    /// function
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-C+struct+to+Ruby+object)
+    ///
+    //+ c-func: gc.c `void rb_gc_mark(VALUE)`
+    pub fn rb_gc_mark(obj: VALUE);
+
+    /// Like [`rb_gc_mark`], but `obj` may be any word-sized value; it is only
+    /// marked if it looks like a pointer to a live object.
+    ///
+    /// * `obj` - a value that may or may not be a Ruby object
+    ///
+    /// # Safety
+    ///
+    /// * See [`rb_gc_mark`](fn.rb_gc_mark.html#safety)
+    ///
+    //+ c-func: gc.c `void rb_gc_mark_maybe(VALUE)`
+    pub fn rb_gc_mark_maybe(obj: VALUE);
+
+    /// Calls [`rb_gc_mark_maybe`] on each `VALUE` in a range of memory.
+    ///
+    /// * `start` - a pointer to the first `VALUE`
+    /// * `end` - a pointer past the last `VALUE`
+    ///
+    /// # Safety
+    ///
+    /// * `start` and `end` must delimit a readable array of `VALUE`s
+    /// * See also [`rb_gc_mark`](fn.rb_gc_mark.html#safety)
+    ///
+    //+ c-func: gc.c `void rb_gc_mark_locations(const VALUE*, const VALUE*)`
+    pub fn rb_gc_mark_locations(start: *const VALUE, end: *const VALUE);
+
+    /// Like [`rb_gc_mark`], but allows `GC.compact` to move the object.
+    ///
+    /// The `dcompact` function of the [`rb_data_type_t`] must then update the
+    /// `VALUE` with [`rb_gc_location`].
+    ///
+    /// Only available with the `ruby_2_7` feature, as it was introduced in
+    /// Ruby 2.7.
+    ///
+    /// * `obj` - any Ruby object
+    ///
+    /// # Safety
+    ///
+    /// * See [`rb_gc_mark`](fn.rb_gc_mark.html#safety)
+    ///
+    /// # Miscellaneous
+    ///
+    /// * Defined in `gc.c` and declared in `ruby/intern.h` since 2.7.
+    #[cfg(feature = "ruby_2_7")]
+    pub fn rb_gc_mark_movable(obj: VALUE);
+
+    /// Returns the new location of an object moved by `GC.compact`, or `obj`
+    /// itself if it hasn't moved.
+    ///
+    /// Only available with the `ruby_2_7` feature, as it was introduced in
+    /// Ruby 2.7.
+    ///
+    /// * `obj` - any Ruby object
+    ///
+    /// # Safety
+    ///
+    /// * Only meaningful while the GC is compacting, i.e. from a `dcompact`
+    /// function; see also [`gc_update_location`](fn.gc_update_location.html)
+    ///
+    /// # Miscellaneous
+    ///
+    /// * Defined in `gc.c` and declared in `ruby/intern.h` since 2.7.
+    #[cfg(feature = "ruby_2_7")]
+    pub fn rb_gc_location(obj: VALUE) -> VALUE;
+
+    /// Starts a full garbage collection.
+    ///
+    /// This is the equivalent of `GC.start`.
+    ///
+    /// * Returns [`nil`](Qnil)
+    ///
+    /// # Safety
+    ///
+    /// * Objects only referenced from Rust code the GC doesn't scan (heap
+    /// allocations, statics) are collected, see [`Rooted`](struct.Rooted.html)
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * **2.5:** [`GC.start`](https://ruby-doc.org/core-2.5.1/GC.html#method-c-start)
+    ///
+    //+ c-func: gc.c `VALUE rb_gc_start(void)`
+    pub fn rb_gc_start() -> VALUE;
+
+    /// Starts a full garbage collection, and runs any pending finalizers.
+    ///
+    /// # Safety
+    ///
+    /// * See [`rb_gc_start`](fn.rb_gc_start.html#safety)
+    ///
+    //+ c-func: gc.c `void rb_gc(void)`
+    pub fn rb_gc();
+
+    /// Enables garbage collection.
+    ///
+    /// This is the equivalent of `GC.enable`.
+    ///
+    /// * Returns [`true`](Qtrue) if garbage collection was disabled
+    ///
+    /// # Safety
+    ///
+    /// No known issues.
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * **2.5:** [`GC.enable`](https://ruby-doc.org/core-2.5.1/GC.html#method-c-enable)
+    ///
+    //+ c-func: gc.c `VALUE rb_gc_enable(void)`
+    pub fn rb_gc_enable() -> VALUE;
+
+    /// Disables garbage collection, until [`rb_gc_enable`].
+    ///
+    /// This is the equivalent of `GC.disable`.
+    ///
+    /// * Returns [`true`](Qtrue) if garbage collection was already disabled
+    ///
+    /// # Safety
+    ///
+    /// No known issues.
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * **2.5:** [`GC.disable`](https://ruby-doc.org/core-2.5.1/GC.html#method-c-disable)
+    ///
+    //+ c-func: gc.c `VALUE rb_gc_disable(void)`
+    pub fn rb_gc_disable() -> VALUE;
+
+    /// Returns the number of times the GC has run.
+    ///
+    /// This is the equivalent of `GC.count`.
+    ///
+    /// # Safety
+    ///
+    /// No known issues.
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * **2.5:** [`GC.count`](https://ruby-doc.org/core-2.5.1/GC.html#method-c-count)
+    ///
+    //+ c-func: gc.c `size_t rb_gc_count(void)`
+    pub fn rb_gc_count() -> size_t;
+
+    /// Returns GC statistics.
+    ///
+    /// This is the equivalent of `GC.stat`.
+    ///
+    /// * `key` - either a [`Symbol`](rb_cSymbol) naming a single statistic,
+    /// such as `:count`, or a [`Hash`](rb_cHash) to fill in with all of them
+    /// * Returns the value of the statistic for a `Symbol`, or `0` for a `Hash`
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`ArgumentError`](rb_eArgError)
+    ///     * if `key` is an unknown `Symbol`
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if `key` is neither a `Symbol` nor a `Hash`
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * **2.5:** [`GC.stat`](https://ruby-doc.org/core-2.5.1/GC.html#method-c-stat)
+    ///
+    //+ c-func: gc.c `size_t rb_gc_stat(VALUE)`
+    pub fn rb_gc_stat(key: VALUE) -> size_t;
+
+    /// Returns information about the latest garbage collection.
+    ///
+    /// This is the equivalent of `GC.latest_gc_info`.
+    ///
+    /// * `key` - either a [`Symbol`](rb_cSymbol) naming a single entry, such
+    /// as `:major_by`, or a [`Hash`](rb_cHash) to fill in with all of them
+    /// * Returns the value of the entry for a `Symbol`, or `key` for a `Hash`
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`ArgumentError`](rb_eArgError)
+    ///     * if `key` is an unknown `Symbol`
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if `key` is neither a `Symbol` nor a `Hash`
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * **2.5:** [`GC.latest_gc_info`](https://ruby-doc.org/core-2.5.1/GC.html#method-c-latest_gc_info)
+    ///
+    //+ c-func: gc.c `VALUE rb_gc_latest_gc_info(VALUE)`
+    pub fn rb_gc_latest_gc_info(key: VALUE) -> VALUE;
+
+    /// Tells the GC about memory allocated (or freed, when negative) outside of
+    /// Ruby's allocator, e.g. by Rust code owned by a Ruby object.
+    ///
+    /// The GC runs sooner as more memory is reported, as it would for memory
+    /// allocated with `ruby_xmalloc`.
+    ///
+    /// * `diff` - the change in memory usage, in bytes
+    ///
+    /// # Safety
+    ///
+    /// No known issues; only adjusts the malloc counters used to decide when
+    /// the next GC runs
+    ///
+    /// # Miscellaneous
+    ///
+    /// * Defined in `gc.c` and declared in `ruby/intern.h` since 2.4.
+    pub fn rb_gc_adjust_memory_usage(diff: ssize_t);
+}
+
+/// Executes a closure on each key-value pair in a hash.
+///
+/// Like [`rb_hash_foreach`], but the state lives in the closure instead of
+/// being passed through `farg`.
+///
+/// * `hash` - a [`Hash`](rb_cHash)
+/// * `func` - called for each key-value pair of the hash, returning an
+/// `st_retval` as described in [`rb_hash_foreach`]
+///
+/// If `func` panics, iteration stops and the panic is resumed once Ruby is
+/// done iterating, so it can be caught by the caller as usual (e.g. by
+/// [`rb_method!`](macro.rb_method.html)).
+///
+/// # Safety
+///
+/// * Undefined behavior if `hash` is not a `Hash`
+/// * If an exception is raised during iteration, Rust frames are unwound
+/// via `longjmp`, so `func` and anything it owns will not be dropped.
+///
+/// ## Exceptions
+///
+/// * See [`rb_hash_foreach`](fn.rb_hash_foreach.html#exceptions)
+pub unsafe fn hash_foreach<F: FnMut(VALUE, VALUE) -> st_retval>(hash: VALUE, func: F) {
+    struct State<F> {
+        func: F,
+        panic: Option<Box<dyn Any + Send>>,
+    }
+
+    extern "C" fn trampoline<F: FnMut(VALUE, VALUE) -> st_retval>(key: VALUE, val: VALUE, farg: VALUE) -> st_retval {
+        let state = unsafe { &mut *farg.as_ptr::<State<F>>() };
+
+        match catch_unwind(AssertUnwindSafe(|| (state.func)(key, val))) {
+            Ok(retval) => retval,
+            Err(payload) => {
+                state.panic = Some(payload);
+                unsafe { ST_STOP }
+            }
+        }
+    }
+
+    let mut state = State { func, panic: None };
+
+    rb_hash_foreach(hash, trampoline::<F>, VALUE::from_ptr(&mut state));
+
+    if let Some(payload) = state.panic {
+        resume_unwind(payload);
+    }
+}
+
+tests! {
+    use super::*;
+    use super::super::testing::{Assertions, ToRuby, lazy_eval};
+    use libc::c_void;
+    use std::ptr::{null, null_mut};
+    use std::slice;
+
+    #[test]
+    fn test_ary_new(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("[]"), unsafe { rb_ary_new() });
+
+        let arr1 = unsafe { rb_ary_new() };
+
+        unsafe { rb_ary_push(arr1, Qtrue) };
+        unsafe { rb_ary_push(arr1, Qfalse) };
+        unsafe { rb_ary_push(arr1, Qnil) };
+
+        assert.rb_eq(lazy_eval("[true, false, nil]"), arr1);
+
+        let arr2 = unsafe { rb_ary_new() };
+
+        unsafe { rb_ary_push(arr2, "hello".to_ruby()) };
+        unsafe { rb_ary_push(arr2, "world!".to_ruby()) };
+
+        assert.rb_eq(lazy_eval("['hello', 'world!']"), arr2);
     }
 
     #[test]
@@ -348,7 +1264,7 @@ tests! {
         let static_str = "static str";
         let static_str_ptr = static_str.as_ptr() as *const c_char;
 
-        let heap_string = format!("heap string");
+        let heap_string = String::from("heap string");
         let heap_string_ptr = heap_string.as_ptr() as *const c_char;
 
         let unicode = "❤️💛💚💙💜";
@@ -359,13 +1275,142 @@ tests! {
         assert.rb_eq(lazy_eval("''"), unsafe { rb_utf8_str_new(heap_string_ptr, 0) });
         assert.rb_eq(lazy_eval("''"), unsafe { rb_utf8_str_new(unicode_ptr, 0) });
 
-        assert.rb_eq(lazy_eval("'static'"), unsafe { rb_utf8_str_new(static_str_ptr, 6) });
-        assert.rb_eq(lazy_eval("'heap'"), unsafe { rb_utf8_str_new(heap_string_ptr, 4) });
-        assert.rb_eq(lazy_eval("'❤️'"), unsafe { rb_utf8_str_new(unicode_ptr, 6) });
+        assert.rb_eq(lazy_eval("'static'"), unsafe { rb_utf8_str_new(static_str_ptr, 6) });
+        assert.rb_eq(lazy_eval("'heap'"), unsafe { rb_utf8_str_new(heap_string_ptr, 4) });
+        assert.rb_eq(lazy_eval("'❤️'"), unsafe { rb_utf8_str_new(unicode_ptr, 6) });
+
+        assert.rb_eq(lazy_eval("'static str'"), unsafe { rb_utf8_str_new(static_str_ptr, 10) });
+        assert.rb_eq(lazy_eval("'heap string'"), unsafe { rb_utf8_str_new(heap_string_ptr, 11) });
+        assert.rb_eq(lazy_eval("'❤️💛💚💙💜'"), unsafe { rb_utf8_str_new(unicode_ptr, 22) });
+    }
+
+    fn encoding_of(string: VALUE) -> VALUE {
+        unsafe { rb_funcall!(string, rb_intern(cstr!("encoding"))) }
+    }
+
+    #[test]
+    fn test_str_new(assert: &mut Assertions) {
+        let bytes = b"\xffbinary\0data";
+        let string = unsafe { rb_str_new(bytes.as_ptr() as *const c_char, bytes.len() as c_long) };
+
+        assert.rb_eq(lazy_eval(r#""\xFFbinary\0data".b"#), string);
+        assert.rb_eq(lazy_eval("Encoding::ASCII_8BIT"), encoding_of(string));
+        assert.rb_eq(lazy_eval("''.b"), unsafe { rb_str_new(null(), 0) });
+
+        let string = unsafe { rb_str_new_cstr(cstr!("c string")) };
+
+        assert.rb_eq(lazy_eval("'c string'.b"), string);
+        assert.rb_eq(lazy_eval("Encoding::ASCII_8BIT"), encoding_of(string));
+
+        let string = unsafe { rb_usascii_str_new(cstr!("ascii"), 5) };
+
+        assert.rb_eq(lazy_eval("'ascii'.encode('US-ASCII')"), string);
+        assert.rb_eq(lazy_eval("Encoding::US_ASCII"), encoding_of(string));
+
+        let string = unsafe { rb_external_str_new(cstr!("external"), 8) };
+
+        assert.rb_eq(lazy_eval("'external'"), string);
+        assert.rb_eq(lazy_eval("Encoding.default_internal || Encoding.default_external"), encoding_of(string));
+    }
+
+    #[test]
+    fn test_str_buf_new(assert: &mut Assertions) {
+        let buf = unsafe { rb_str_buf_new(64) };
+
+        assert.rb_eq(lazy_eval("''.b"), buf);
+        assert.rb_eq(buf, unsafe { rb_str_cat(buf, cstr!("foo"), 3) });
+        assert.rb_eq(buf, unsafe { rb_str_cat(buf, cstr!("bar\0baz"), 7) });
+        assert.rb_eq(lazy_eval(r#""foobar\0baz".b"#), buf);
+    }
+
+    #[test]
+    fn test_str_append(assert: &mut Assertions) {
+        let string = "foo".to_ruby();
+
+        assert.rb_eq(string, unsafe { rb_str_append(string, "bar".to_ruby()) });
+        assert.rb_eq(lazy_eval("'foobar'"), string);
+
+        assert.rb_eq(string, unsafe { rb_str_concat(string, "baz".to_ruby()) });
+        assert.rb_eq(string, unsafe { rb_str_concat(string, INT2NUM(0x2764)) });
+        assert.rb_eq(lazy_eval("'foobarbaz❤'"), string);
+    }
+
+    #[test]
+    fn test_str_new_frozen(assert: &mut Assertions) {
+        let string = "foo".to_ruby();
+        let frozen = unsafe { rb_str_new_frozen(string) };
+
+        assert.rb_eq(lazy_eval("'foo'"), frozen);
+        assert.rb_eq(unsafe { Qtrue }, unsafe { rb_funcall!(frozen, rb_intern(cstr!("frozen?"))) });
+        assert.rb_eq(unsafe { Qfalse }, unsafe { rb_funcall!(string, rb_intern(cstr!("frozen?"))) });
+        assert.rs_eq(frozen, unsafe { rb_str_new_frozen(frozen) });
+    }
+
+    #[test]
+    fn test_str_modify_expand(assert: &mut Assertions) {
+        let string = unsafe { rb_str_new(cstr!("foo"), 3) };
+
+        unsafe {
+            rb_str_modify_expand(string, 64);
+
+            let ptr = RSTRING_PTR_MUT(string).add(RSTRING_LEN(string) as usize);
+            slice::from_raw_parts_mut(ptr as *mut u8, 64).copy_from_slice(&[b'!'; 64]);
+
+            rb_str_set_len(string, 3 + 64);
+        }
+
+        assert.rb_eq(lazy_eval("('foo' + '!' * 64).b"), string);
+    }
+
+    #[test]
+    fn test_str_modify(assert: &mut Assertions) {
+        let original = "a string that is long enough to be shared".to_ruby();
+        let copy = unsafe { rb_funcall!(original, rb_intern(cstr!("dup"))) };
+
+        unsafe {
+            rb_str_modify(copy);
+            *RSTRING_PTR_MUT(copy) = b'A' as c_char;
+        }
+
+        assert.rb_eq(lazy_eval("'a string that is long enough to be shared'"), original);
+        assert.rb_eq(lazy_eval("'A string that is long enough to be shared'"), copy);
+
+        extern "C" fn __test_str_modify__(_self: VALUE, string: VALUE) -> VALUE {
+            unsafe { rb_str_modify(string) };
+            string
+        }
+
+        unsafe {
+            define_method(
+                rb_mKernel,
+                cstr!("__test_str_modify__"),
+                METHOD_FUNC::arity_1(__test_str_modify__)
+            );
+        }
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_str_modify__('foo'.freeze)
+                rescue Exception => e
+                e.class == (defined?(FrozenError) ? FrozenError : RuntimeError)
+                end
+            "#),
+            unsafe { Qtrue }
+        );
+    }
+
+    #[test]
+    fn test_str_resize(assert: &mut Assertions) {
+        let string = "foobar".to_ruby();
+
+        assert.rb_eq(string, unsafe { rb_str_resize(string, 3) });
+        assert.rb_eq(lazy_eval("'foo'"), string);
+
+        unsafe { rb_str_resize(string, 32) };
 
-        assert.rb_eq(lazy_eval("'static str'"), unsafe { rb_utf8_str_new(static_str_ptr, 10) });
-        assert.rb_eq(lazy_eval("'heap string'"), unsafe { rb_utf8_str_new(heap_string_ptr, 11) });
-        assert.rb_eq(lazy_eval("'❤️💛💚💙💜'"), unsafe { rb_utf8_str_new(unicode_ptr, 22) });
+        assert.rs_eq(32, unsafe { RSTRING_LEN(string) });
+        assert.rs_eq(&b"foo"[..], unsafe { slice::from_raw_parts(RSTRING_PTR(string) as *const u8, 3) });
     }
 
     #[test]
@@ -412,6 +1457,68 @@ tests! {
         assert.rb_eq(lazy_eval(r#"{"foo"=>"bar", "baz"=>"qux", "wibble"=>"wobble", "eggs"=>"spam"}"#), hash);
     }
 
+    #[test]
+    fn test_hash_foreach_closure(assert: &mut Assertions) {
+        let hash = unsafe { rb_hash_new() };
+
+        unsafe {
+            rb_hash_aset(hash, "foo".to_ruby(), "bar".to_ruby());
+            rb_hash_aset(hash, "baz".to_ruby(), "qux".to_ruby());
+            rb_hash_aset(hash, "hoge".to_ruby(), "piyo".to_ruby());
+            rb_hash_aset(hash, "wibble".to_ruby(), "wobble".to_ruby());
+        }
+
+        let mut keys = Vec::new();
+
+        unsafe {
+            hash_foreach(hash, |key, _val| {
+                let id = rb_intern_str(key);
+                keys.push(id);
+
+                if id == rb_intern(cstr!("hoge")) { st::ST_DELETE } else { st::ST_CONTINUE }
+            });
+        }
+
+        assert.rs_eq(unsafe { vec![rb_intern(cstr!("foo")), rb_intern(cstr!("baz")), rb_intern(cstr!("hoge")), rb_intern(cstr!("wibble"))] }, keys);
+        assert.rb_eq(lazy_eval(r#"{"foo"=>"bar", "baz"=>"qux", "wibble"=>"wobble"}"#), hash);
+
+        let mut count = 0;
+
+        unsafe {
+            hash_foreach(hash, |_key, _val| {
+                count += 1;
+                st::ST_STOP
+            });
+        }
+
+        assert.rs_eq(1, count);
+    }
+
+    #[test]
+    fn test_hash_foreach_panic(assert: &mut Assertions) {
+        let hash = unsafe { rb_hash_new() };
+
+        unsafe {
+            rb_hash_aset(hash, "foo".to_ruby(), "bar".to_ruby());
+            rb_hash_aset(hash, "baz".to_ruby(), "qux".to_ruby());
+        }
+
+        let mut count = 0;
+
+        let result = catch_unwind(AssertUnwindSafe(|| unsafe {
+            hash_foreach(hash, |_key, _val| {
+                count += 1;
+                panic!("__test_hash_foreach_panic__ panicked")
+            });
+        }));
+
+        let message = result.err().and_then(|payload| payload.downcast_ref::<&str>().map(|message| message.to_string()));
+
+        assert.rs_eq(Some(String::from("__test_hash_foreach_panic__ panicked")), message);
+        assert.rs_eq(1, count);
+        assert.rb_eq(lazy_eval(r#"{"foo"=>"bar", "baz"=>"qux"}"#), hash);
+    }
+
     #[test]
     fn test_class_new_instance(assert: &mut Assertions) {
         assert.rb_ne(lazy_eval("Object.new"), unsafe { rb_class_new_instance(0, null(), rb_cObject) });
@@ -561,5 +1668,556 @@ tests! {
             "__test_define_singleton_method_arity_3__ works!".to_ruby()
         );
     }
-}
 
+    #[test]
+    fn test_exc_new(assert: &mut Assertions) {
+        let message = "something went wrong";
+        let ptr = message.as_ptr() as *const c_char;
+        let len = message.len() as c_long;
+
+        let exc1 = unsafe { rb_exc_new(rb_eArgError, ptr, len) };
+        let exc2 = unsafe { rb_exc_new(rb_eArgError, ptr, 9) };
+        let exc3 = unsafe { rb_exc_new_cstr(rb_eTypeError, cstr!("something went wrong")) };
+        let exc4 = unsafe { rb_exc_new_str(rb_eRuntimeError, message.to_ruby()) };
+
+        assert.rb_eq(unsafe { rb_eArgError }, unsafe { rb_obj_class(exc1) });
+        assert.rb_eq(unsafe { rb_eArgError }, unsafe { rb_obj_class(exc2) });
+        assert.rb_eq(unsafe { rb_eTypeError }, unsafe { rb_obj_class(exc3) });
+        assert.rb_eq(unsafe { rb_eRuntimeError }, unsafe { rb_obj_class(exc4) });
+
+        assert.rb_eq(lazy_eval("ArgumentError.new('something went wrong').inspect"), unsafe { rb_inspect(exc1) });
+        assert.rb_eq(lazy_eval("ArgumentError.new('something').inspect"), unsafe { rb_inspect(exc2) });
+        assert.rb_eq(lazy_eval("TypeError.new('something went wrong').inspect"), unsafe { rb_inspect(exc3) });
+        assert.rb_eq(lazy_eval("RuntimeError.new('something went wrong').inspect"), unsafe { rb_inspect(exc4) });
+    }
+
+    #[test]
+    fn test_exc_raise(assert: &mut Assertions) {
+        extern "C" fn __test_exc_raise__(_self: VALUE) -> VALUE {
+            unsafe {
+                let exception = rb_exc_new_str(rb_eIndexError, "__test_exc_raise__ raised".to_ruby());
+                rb_exc_raise(exception)
+            }
+        }
+
+        unsafe {
+            rb_define_method(
+                rb_mKernel,
+                cstr!("__test_exc_raise__"),
+                ANYARGS::from_arity_1(__test_exc_raise__),
+                0
+            );
+        }
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_exc_raise__
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eIndexError }
+        );
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_exc_raise__
+                rescue Exception => e
+                e.message
+                end
+            "#),
+            "__test_exc_raise__ raised".to_ruby()
+        );
+    }
+
+    #[test]
+    fn test_gc_mark(assert: &mut Assertions) {
+        extern "C" fn __test_gc_mark__(data: *mut c_void) {
+            let values = unsafe { &*(data as *const [VALUE; 4]) };
+
+            unsafe {
+                rb_gc_mark(values[0]);
+                rb_gc_mark_maybe(values[1]);
+                rb_gc_mark_locations(&values[2], values.as_ptr().add(4));
+            }
+        }
+
+        extern "C" fn __test_gc_mark_free__(data: *mut c_void) {
+            drop(unsafe { Box::from_raw(data as *mut [VALUE; 4]) });
+        }
+
+        static GC_MARK_TYPE: rb_data_type_t = rb_data_type_t {
+            wrap_struct_name: cstr!("TestGcMark"),
+            function: rb_data_type_function_t {
+                dmark: Some(__test_gc_mark__),
+                dfree: Some(__test_gc_mark_free__),
+                dsize: None,
+                dcompact: None,
+                reserved: [null_mut()],
+            },
+            parent: null(),
+            data: null_mut(),
+            flags: RUBY_TYPED_FREE_IMMEDIATELY,
+        };
+
+        #[inline(never)]
+        fn new_string(s: &str) -> VALUE {
+            s.to_ruby()
+        }
+
+        let values = Box::new([new_string("foo"), new_string("bar"), new_string("baz"), new_string("qux")]);
+        let values = Box::into_raw(values);
+        let obj = unsafe { rb_data_typed_object_wrap(rb_cObject, values as *mut c_void, &GC_MARK_TYPE) };
+
+        unsafe { rb_gc_start() };
+
+        let values = unsafe { &*(RTYPEDDATA_DATA(obj) as *const [VALUE; 4]) };
+
+        assert.rb_eq(lazy_eval("'foo'"), values[0]);
+        assert.rb_eq(lazy_eval("'bar'"), values[1]);
+        assert.rb_eq(lazy_eval("'baz'"), values[2]);
+        assert.rb_eq(lazy_eval("'qux'"), values[3]);
+    }
+
+    #[test]
+    fn test_gc_start(assert: &mut Assertions) {
+        let count = unsafe { rb_gc_count() };
+
+        assert.rb_nil(unsafe { rb_gc_start() });
+
+        let after_start = unsafe { rb_gc_count() };
+        assert.rs_eq(true, after_start > count);
+
+        unsafe { rb_gc() };
+
+        assert.rs_eq(true, unsafe { rb_gc_count() } > after_start);
+    }
+
+    #[test]
+    fn test_gc_enable_disable(assert: &mut Assertions) {
+        assert.rb_eq(unsafe { Qfalse }, unsafe { rb_gc_disable() });
+        assert.rb_eq(unsafe { Qtrue }, unsafe { rb_gc_disable() });
+        assert.rb_eq(unsafe { Qtrue }, unsafe { rb_gc_enable() });
+        assert.rb_eq(unsafe { Qfalse }, unsafe { rb_gc_enable() });
+    }
+
+    #[test]
+    fn test_gc_stat(assert: &mut Assertions) {
+        let count = unsafe { rb_id2sym(rb_intern(cstr!("count"))) };
+
+        assert.rs_eq(unsafe { rb_gc_count() }, unsafe { rb_gc_stat(count) });
+
+        let hash = unsafe { rb_hash_new() };
+
+        assert.rs_eq(0, unsafe { rb_gc_stat(hash) });
+        assert.rb_eq(unsafe { Qtrue }, unsafe { rb_funcall!(hash, rb_intern(cstr!("key?")), count) });
+    }
+
+    #[test]
+    fn test_gc_latest_gc_info(assert: &mut Assertions) {
+        unsafe { rb_gc_start() };
+
+        let major_by = unsafe { rb_id2sym(rb_intern(cstr!("major_by"))) };
+        let hash = unsafe { rb_hash_new() };
+
+        assert.rb_eq(hash, unsafe { rb_gc_latest_gc_info(hash) });
+        assert.rb_eq(unsafe { rb_funcall!(hash, rb_intern(cstr!("[]")), major_by) }, unsafe { rb_gc_latest_gc_info(major_by) });
+    }
+
+    #[test]
+    fn test_gc_adjust_memory_usage(assert: &mut Assertions) {
+        let malloc_increase_bytes = unsafe { rb_id2sym(rb_intern(cstr!("malloc_increase_bytes"))) };
+
+        // Prevent the reported memory from triggering a GC, which would reset the counter
+        unsafe { rb_gc_disable() };
+
+        let before = unsafe { rb_gc_stat(malloc_increase_bytes) };
+        unsafe { rb_gc_adjust_memory_usage(1024) };
+        let after = unsafe { rb_gc_stat(malloc_increase_bytes) };
+        unsafe { rb_gc_adjust_memory_usage(-1024) };
+
+        unsafe { rb_gc_enable() };
+
+        assert.rs_eq(true, after >= before + 1024);
+    }
+
+    #[test]
+    fn test_define_class_id_under(assert: &mut Assertions) {
+        let outer = unsafe { rb_define_module(cstr!("TestDefineClassIdUnder__Outer")) };
+        let inner = unsafe { rb_define_class_id_under(outer, rb_intern(cstr!("Inner")), rb_cObject) };
+
+        assert.rb_eq(lazy_eval("::TestDefineClassIdUnder__Outer::Inner"), inner);
+        assert.rb_eq(lazy_eval("::TestDefineClassIdUnder__Outer::Inner.superclass"), unsafe { rb_cObject });
+    }
+
+    #[test]
+    fn test_class_new(assert: &mut Assertions) {
+        let class = unsafe { rb_class_new(rb_cArray) };
+
+        assert.rb_eq(unsafe { rb_cClass }, unsafe { rb_obj_class(class) });
+        assert.rb_eq(unsafe { rb_cArray }, unsafe { rb_funcall!(class, rb_intern(cstr!("superclass"))) });
+        assert.rb_nil(unsafe { rb_funcall!(class, rb_intern(cstr!("name"))) });
+
+        let module = unsafe { rb_module_new() };
+
+        assert.rb_eq(unsafe { rb_cModule }, unsafe { rb_obj_class(module) });
+        assert.rb_nil(unsafe { rb_funcall!(module, rb_intern(cstr!("name"))) });
+    }
+
+    #[test]
+    fn test_define_method_visibility(assert: &mut Assertions) {
+        extern "C" fn __test_visibility__(_self: VALUE) -> VALUE {
+            "__test_visibility__ works!".to_ruby()
+        }
+
+        let class = unsafe { rb_define_class(cstr!("TestMethodVisibility__Foo"), rb_cObject) };
+
+        unsafe {
+            rb_define_private_method(class, cstr!("private_method"), ANYARGS::from_arity_1(__test_visibility__), 0);
+            rb_define_protected_method(class, cstr!("protected_method"), ANYARGS::from_arity_1(__test_visibility__), 0);
+            rb_define_method_id(class, rb_intern(cstr!("public_method_id")), ANYARGS::from_arity_1(__test_visibility__), 0);
+
+            define_private_method(class, cstr!("typed_private_method"), METHOD_FUNC::arity_0(__test_visibility__));
+            define_protected_method(class, cstr!("typed_protected_method"), METHOD_FUNC::arity_0(__test_visibility__));
+            define_method_id(class, rb_intern(cstr!("typed_public_method_id")), METHOD_FUNC::arity_0(__test_visibility__));
+        }
+
+        assert.rb_eq(lazy_eval("TestMethodVisibility__Foo.private_method_defined?(:private_method)"), unsafe { Qtrue });
+        assert.rb_eq(lazy_eval("TestMethodVisibility__Foo.protected_method_defined?(:protected_method)"), unsafe { Qtrue });
+        assert.rb_eq(lazy_eval("TestMethodVisibility__Foo.public_method_defined?(:public_method_id)"), unsafe { Qtrue });
+
+        assert.rb_eq(lazy_eval("TestMethodVisibility__Foo.private_method_defined?(:typed_private_method)"), unsafe { Qtrue });
+        assert.rb_eq(lazy_eval("TestMethodVisibility__Foo.protected_method_defined?(:typed_protected_method)"), unsafe { Qtrue });
+        assert.rb_eq(lazy_eval("TestMethodVisibility__Foo.public_method_defined?(:typed_public_method_id)"), unsafe { Qtrue });
+
+        assert.rb_eq(lazy_eval("TestMethodVisibility__Foo.new.send(:private_method)"), "__test_visibility__ works!".to_ruby());
+        assert.rb_eq(lazy_eval("TestMethodVisibility__Foo.new.public_method_id"), "__test_visibility__ works!".to_ruby());
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                TestMethodVisibility__Foo.new.private_method
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eNoMethodError }
+        );
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                TestMethodVisibility__Foo.new.protected_method
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eNoMethodError }
+        );
+    }
+
+    #[test]
+    fn test_alias_and_remove_method(assert: &mut Assertions) {
+        let class = unsafe { rb_define_class(cstr!("TestAlias__Foo"), rb_cString) };
+
+        unsafe {
+            rb_define_alias(class, cstr!("shout"), cstr!("upcase"));
+            rb_alias(class, rb_intern(cstr!("whisper")), rb_intern(cstr!("downcase")));
+            rb_define_attr(class, cstr!("reader"), 1, 0);
+            rb_define_attr(class, cstr!("writer"), 0, 1);
+            rb_define_attr(class, cstr!("accessor"), 1, 1);
+        }
+
+        assert.rb_eq(lazy_eval("TestAlias__Foo.new('Foo').shout"), "FOO".to_ruby());
+        assert.rb_eq(lazy_eval("TestAlias__Foo.new('Foo').whisper"), "foo".to_ruby());
+        assert.rb_eq(lazy_eval("TestAlias__Foo.instance_method(:shout).original_name"), unsafe { rb_id2sym(rb_intern(cstr!("upcase"))) });
+
+        assert.rb_eq(lazy_eval("TestAlias__Foo.public_method_defined?(:reader)"), unsafe { Qtrue });
+        assert.rb_eq(lazy_eval("TestAlias__Foo.public_method_defined?(:reader=)"), unsafe { Qfalse });
+        assert.rb_eq(lazy_eval("TestAlias__Foo.public_method_defined?(:writer)"), unsafe { Qfalse });
+        assert.rb_eq(lazy_eval("TestAlias__Foo.public_method_defined?(:writer=)"), unsafe { Qtrue });
+        assert.rb_eq(lazy_eval("TestAlias__Foo.new.tap { |foo| foo.accessor = 42 }.accessor"), unsafe { INT2NUM(42) });
+
+        unsafe {
+            rb_define_alias(class, cstr!("length"), cstr!("upcase"));
+            rb_remove_method(class, cstr!("length"));
+        }
+
+        assert.rb_eq(lazy_eval("TestAlias__Foo.new('Foo').length"), unsafe { INT2NUM(3) });
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                TestAlias__Foo.send(:remove_method, :size)
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eNameError }
+        );
+    }
+
+    #[test]
+    fn test_define_alloc_func(assert: &mut Assertions) {
+        struct AllocCounter(c_int);
+
+        impl HeapSize for AllocCounter {}
+
+        typed_data!(AllocCounter);
+
+        extern "C" fn __test_alloc_func__(class: VALUE) -> VALUE {
+            unsafe { TypedData::wrap(class, AllocCounter(0)) }
+        }
+
+        extern "C" fn __test_alloc_func_initialize__(this: VALUE, count: VALUE) -> VALUE {
+            unsafe {
+                match TypedData::<AllocCounter>::get_mut(&this) {
+                    Ok(counter) => counter.0 = NUM2INT(count),
+                    Err(error) => error.raise(),
+                }
+
+                Qnil
+            }
+        }
+
+        extern "C" fn __test_alloc_func_initialize_copy__(this: VALUE, orig: VALUE) -> VALUE {
+            unsafe {
+                rb_obj_init_copy(this, orig);
+
+                let count = match TypedData::<AllocCounter>::get(&orig) {
+                    Ok(counter) => counter.0,
+                    Err(error) => error.raise(),
+                };
+
+                match TypedData::<AllocCounter>::get_mut(&this) {
+                    Ok(counter) => counter.0 = count,
+                    Err(error) => error.raise(),
+                }
+
+                this
+            }
+        }
+
+        extern "C" fn __test_alloc_func_count__(this: VALUE) -> VALUE {
+            let count = match unsafe { TypedData::<AllocCounter>::get(&this) } {
+                Ok(counter) => counter.0,
+                Err(error) => unsafe { error.raise() },
+            };
+
+            unsafe { INT2NUM(count) }
+        }
+
+        let class = unsafe { rb_define_class(cstr!("TestAllocFunc__Counter"), rb_cObject) };
+
+        unsafe {
+            rb_define_alloc_func(class, __test_alloc_func__);
+            define_method(class, cstr!("initialize"), METHOD_FUNC::arity_1(__test_alloc_func_initialize__));
+            define_method(class, cstr!("initialize_copy"), METHOD_FUNC::arity_1(__test_alloc_func_initialize_copy__));
+            define_method(class, cstr!("count"), METHOD_FUNC::arity_0(__test_alloc_func_count__));
+        }
+
+        assert.rb_eq(lazy_eval("TestAllocFunc__Counter.allocate.count"), unsafe { INT2NUM(0) });
+        assert.rb_eq(lazy_eval("TestAllocFunc__Counter.new(42).count"), unsafe { INT2NUM(42) });
+        assert.rb_eq(lazy_eval("TestAllocFunc__Counter.new(42).dup.count"), unsafe { INT2NUM(42) });
+        assert.rb_eq(lazy_eval("TestAllocFunc__Counter.new(42).clone.count"), unsafe { INT2NUM(42) });
+
+        let obj = unsafe { rb_obj_alloc(class) };
+
+        assert.rb_eq(unsafe { INT2NUM(0) }, unsafe { rb_funcall!(obj, rb_intern(cstr!("count"))) });
+
+        unsafe { rb_obj_call_init(obj, 1, &INT2NUM(7)) };
+
+        assert.rb_eq(unsafe { INT2NUM(7) }, unsafe { rb_funcall!(obj, rb_intern(cstr!("count"))) });
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                TestAllocFunc__Counter.new(1).send(:initialize_copy, Object.new)
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eTypeError }
+        );
+    }
+
+    #[test]
+    fn test_undef_alloc_func(assert: &mut Assertions) {
+        let class = unsafe { rb_define_class(cstr!("TestUndefAllocFunc__Foo"), rb_cObject) };
+
+        unsafe { rb_undef_alloc_func(class) };
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                TestUndefAllocFunc__Foo.new
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eTypeError }
+        );
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                TestUndefAllocFunc__Foo.allocate
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eTypeError }
+        );
+    }
+
+    #[test]
+    fn test_include_module(assert: &mut Assertions) {
+        struct Version(c_int);
+
+        impl HeapSize for Version {}
+
+        typed_data!(Version);
+
+        extern "C" fn __test_include_module_alloc__(class: VALUE) -> VALUE {
+            unsafe { TypedData::wrap(class, Version(0)) }
+        }
+
+        extern "C" fn __test_include_module_initialize__(this: VALUE, version: VALUE) -> VALUE {
+            unsafe {
+                match TypedData::<Version>::get_mut(&this) {
+                    Ok(this) => this.0 = NUM2INT(version),
+                    Err(error) => error.raise(),
+                }
+
+                Qnil
+            }
+        }
+
+        extern "C" fn __test_include_module_cmp__(this: VALUE, other: VALUE) -> VALUE {
+            unsafe {
+                let this = match TypedData::<Version>::get(&this) {
+                    Ok(this) => this.0,
+                    Err(error) => error.raise(),
+                };
+
+                match TypedData::<Version>::get(&other) {
+                    Ok(other) => INT2NUM(this.cmp(&other.0) as c_int),
+                    Err(_) => Qnil,
+                }
+            }
+        }
+
+        extern "C" fn __test_include_module_each__(this: VALUE) -> VALUE {
+            unsafe {
+                let version = match TypedData::<Version>::get(&this) {
+                    Ok(this) => this.0,
+                    Err(error) => error.raise(),
+                };
+
+                for i in 0..version {
+                    rb_yield(INT2NUM(i));
+                }
+
+                this
+            }
+        }
+
+        let class = unsafe { rb_define_class(cstr!("TestIncludeModule__Version"), rb_cObject) };
+
+        unsafe {
+            rb_define_alloc_func(class, __test_include_module_alloc__);
+            define_method(class, cstr!("initialize"), METHOD_FUNC::arity_1(__test_include_module_initialize__));
+            define_method(class, cstr!("<=>"), METHOD_FUNC::arity_1(__test_include_module_cmp__));
+            define_method(class, cstr!("each"), METHOD_FUNC::arity_0(__test_include_module_each__));
+        }
+
+        assert.rb_eq(unsafe { Qfalse }, unsafe { rb_mod_include_p(class, rb_mComparable) });
+
+        unsafe {
+            rb_include_module(class, rb_mComparable);
+            rb_include_module(class, rb_mEnumerable);
+        }
+
+        assert.rb_eq(unsafe { Qtrue }, unsafe { rb_mod_include_p(class, rb_mComparable) });
+        assert.rb_eq(unsafe { Qtrue }, unsafe { rb_mod_include_p(class, rb_mEnumerable) });
+        assert.rb_eq(lazy_eval("TestIncludeModule__Version.ancestors"), unsafe { rb_mod_ancestors(class) });
+        assert.rb_eq(lazy_eval("TestIncludeModule__Version.ancestors.take(3)"), lazy_eval("[TestIncludeModule__Version, Enumerable, Comparable]"));
+
+        assert.rb_eq(lazy_eval("TestIncludeModule__Version.new(1) < TestIncludeModule__Version.new(2)"), unsafe { Qtrue });
+        assert.rb_eq(lazy_eval("TestIncludeModule__Version.new(2).between?(TestIncludeModule__Version.new(1), TestIncludeModule__Version.new(3))"), unsafe { Qtrue });
+        assert.rb_eq(lazy_eval("TestIncludeModule__Version.new(3).to_a"), lazy_eval("[0, 1, 2]"));
+        assert.rb_eq(lazy_eval("TestIncludeModule__Version.new(3).map { |i| i * 2 }"), lazy_eval("[0, 2, 4]"));
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                TestIncludeModule__Version.new(1) < Object.new
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eArgError }
+        );
+    }
+
+    #[test]
+    fn test_prepend_module(assert: &mut Assertions) {
+        extern "C" fn __test_prepend_module_original__(_self: VALUE) -> VALUE {
+            "original".to_ruby()
+        }
+
+        extern "C" fn __test_prepend_module_prepended__(_self: VALUE) -> VALUE {
+            "prepended".to_ruby()
+        }
+
+        let class = unsafe { rb_define_class(cstr!("TestPrependModule__Foo"), rb_cObject) };
+        let module = unsafe { rb_define_module(cstr!("TestPrependModule__Bar")) };
+
+        unsafe {
+            define_method(class, cstr!("name"), METHOD_FUNC::arity_0(__test_prepend_module_original__));
+            define_method(module, cstr!("name"), METHOD_FUNC::arity_0(__test_prepend_module_prepended__));
+            rb_prepend_module(class, module);
+        }
+
+        assert.rb_eq(unsafe { Qtrue }, unsafe { rb_mod_include_p(class, module) });
+        assert.rb_eq(lazy_eval("TestPrependModule__Foo.ancestors.take(2)"), lazy_eval("[TestPrependModule__Bar, TestPrependModule__Foo]"));
+        assert.rb_eq(lazy_eval("TestPrependModule__Foo.new.name"), "prepended".to_ruby());
+    }
+
+    #[test]
+    fn test_extend_object(assert: &mut Assertions) {
+        extern "C" fn __test_extend_object__(_self: VALUE) -> VALUE {
+            "extended".to_ruby()
+        }
+
+        let module = unsafe { rb_define_module(cstr!("TestExtendObject__Foo")) };
+        let obj = unsafe { rb_obj_alloc(rb_cObject) };
+
+        unsafe {
+            define_method(module, cstr!("__test_extend_object__"), METHOD_FUNC::arity_0(__test_extend_object__));
+            rb_extend_object(obj, module);
+        }
+
+        let singleton_class = unsafe { rb_singleton_class(obj) };
+
+        assert.rb_ne(unsafe { rb_obj_class(obj) }, singleton_class);
+        assert.rb_eq(singleton_class, unsafe { rb_funcall!(obj, rb_intern(cstr!("singleton_class"))) });
+        assert.rb_eq(unsafe { Qtrue }, unsafe { rb_mod_include_p(singleton_class, module) });
+        assert.rb_eq(unsafe { Qfalse }, unsafe { rb_mod_include_p(rb_cObject, module) });
+        assert.rb_eq("extended".to_ruby(), unsafe { rb_funcall!(obj, rb_intern(cstr!("__test_extend_object__"))) });
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                1.singleton_class
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eTypeError }
+        );
+    }
+
+}
@@ -1,4 +1,6 @@
 #![recursion_limit="1024"]
+#![allow(clippy::doc_overindented_list_items, clippy::doc_lazy_continuation)]
+#![cfg_attr(test, allow(ambiguous_glob_reexports))]
 
 extern crate libc;
 
@@ -24,6 +26,15 @@ macro_rules! tests {
     ( $( $item:tt )* ) => {}
 }
 
+// Counts the arguments of `rb_funcall!`. It lives outside of `init!` so that
+// it can be reached through `$crate` from the crate's own tests.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __rb_count_args {
+    () => { 0 };
+    ( $item:tt $($rest:tt)* ) => { 1 + $crate::__rb_count_args!($($rest)*) };
+}
+
 #[cfg(test)]
 macro_rules! init {
     ( $( $item:tt )* ) => { rb_init! { $($item)* } }
@@ -35,13 +46,25 @@ macro_rules! init {
 }
 
 init! {
-    mod ruby;
+    #[macro_use] mod ruby;
     mod intern;
     mod encoding;
     mod st;
+    #[macro_use] mod unwind;
+    mod scan_args;
+    mod typed_data;
+    mod gc;
+    mod rstring;
+    #[cfg(feature = "global_allocator")] mod allocator;
 
     pub use ruby::*;
     pub use intern::*;
     pub use encoding::*;
     pub use st::*;
+    pub use unwind::*;
+    pub use scan_args::*;
+    pub use typed_data::*;
+    pub use gc::*;
+    pub use rstring::*;
+    #[cfg(feature = "global_allocator")] pub use allocator::*;
 }
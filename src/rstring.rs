@@ -0,0 +1,176 @@
+use super::*;
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::{slice, str};
+
+/// A borrowed view of the contents of a Ruby `String`.
+///
+/// As long as the guard itself lives on the stack, it keeps the `VALUE` there
+/// until it is dropped (see [`gc_guard`]), so the string can't be collected
+/// while its contents are borrowed.
+///
+/// ```ignore
+/// let string = RStringRef::new(value)?;
+///
+/// match string.as_str() {
+///     Ok(s) => println!("{}", s),
+///     Err(_) => println!("{:?}", string.as_bytes()),
+/// }
+/// ```
+///
+/// Guards are neither `Send` nor `Sync`, as Ruby strings may only be accessed
+/// from a Ruby thread.
+#[derive(Debug)]
+pub struct RStringRef {
+    value: VALUE,
+    _marker: PhantomData<*mut VALUE>,
+}
+
+impl RStringRef {
+    /// Borrows the contents of `value`.
+    ///
+    /// * `value` - any Ruby object
+    /// * Returns the guard, or a [`TypeError`](rb_eTypeError) if `value` is
+    /// not a `String`
+    ///
+    /// # Safety
+    ///
+    /// * Must be called on a Ruby thread
+    /// * The string must not be modified while the guard is alive, including
+    /// by Ruby code called in the meantime
+    /// * The guard must not be moved to the heap (e.g. into a `Box` or a
+    /// `Vec`) or leaked, as the GC only sees the `VALUE` while it is on the
+    /// stack
+    pub unsafe fn new(value: VALUE) -> Result<Self, Error> {
+        if RB_TYPE_P(value, T_STRING) == 0 {
+            let class = CStr::from_ptr(rb_obj_classname(value)).to_string_lossy();
+
+            return Err(Error::new(rb_eTypeError, format!("wrong argument type {} (expected String)", class)));
+        }
+
+        Ok(RStringRef { value, _marker: PhantomData })
+    }
+
+    /// Returns the borrowed `String`.
+    pub fn value(&self) -> VALUE {
+        self.value
+    }
+
+    /// Returns the bytes of the string, regardless of its encoding.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            let len = RSTRING_LEN(self.value);
+
+            if len == 0 {
+                return &[];
+            }
+
+            slice::from_raw_parts(RSTRING_PTR(self.value) as *const u8, len as usize)
+        }
+    }
+
+    /// Returns the contents of the string as a `&str`.
+    ///
+    /// * Returns the string, an
+    /// [`Encoding::CompatibilityError`](rb_eEncCompatError) if it is neither
+    /// UTF-8 nor ASCII-only in an ASCII-compatible encoding, or an
+    /// [`ArgumentError`](rb_eArgError) if it is UTF-8 but contains invalid
+    /// byte sequences
+    pub fn as_str(&self) -> Result<&str, Error> {
+        unsafe {
+            if rb_enc_get_index(self.value) == rb_utf8_encindex() {
+                if rb_enc_str_coderange(self.value) == ENC_CODERANGE_BROKEN {
+                    return Err(Error::new(rb_eArgError, "invalid byte sequence in UTF-8"));
+                }
+            } else if rb_enc_str_asciionly_p(self.value) == 0 {
+                return Err(Error::new(rb_eEncCompatError, "incompatible character encoding (expected UTF-8 or ASCII-only)"));
+            }
+
+            Ok(str::from_utf8_unchecked(self.as_bytes()))
+        }
+    }
+}
+
+impl Drop for RStringRef {
+    fn drop(&mut self) {
+        gc_guard(&self.value);
+    }
+}
+
+tests! {
+    use super::*;
+    use super::super::testing::{Assertions, ToRuby, lazy_eval};
+    use libc::c_char;
+    use std::ptr::null;
+
+    #[test]
+    fn test_rstring_ref_as_bytes(assert: &mut Assertions) {
+        let string = unsafe { RStringRef::new("❤️ ruby".to_ruby()) }.unwrap();
+
+        assert.rs_eq("❤️ ruby".as_bytes(), string.as_bytes());
+
+        let string = unsafe { RStringRef::new(rb_str_new(b"\xff\0".as_ptr() as *const c_char, 2)) }.unwrap();
+
+        assert.rs_eq(&b"\xff\0"[..], string.as_bytes());
+
+        let string = unsafe { RStringRef::new(rb_str_new(null(), 0)) }.unwrap();
+
+        assert.rs_eq(&b""[..], string.as_bytes());
+
+        let error = unsafe { RStringRef::new(INT2NUM(42)) }.err().unwrap();
+
+        assert.rb_eq(unsafe { rb_eTypeError }, error.class());
+        assert.rs_eq("wrong argument type Integer (expected String)", error.message());
+    }
+
+    #[test]
+    fn test_rstring_ref_as_str(assert: &mut Assertions) {
+        extern "C" fn __test_rstring_ref_as_str__(_self: VALUE, value: VALUE) -> VALUE {
+            let result = unsafe { RStringRef::new(value) }.and_then(|string| string.as_str().map(|s| s.to_ruby()));
+
+            match result {
+                Ok(value) => value,
+                Err(error) => unsafe { error.raise() },
+            }
+        }
+
+        unsafe {
+            define_method(
+                rb_mKernel,
+                cstr!("__test_rstring_ref_as_str__"),
+                METHOD_FUNC::arity_1(__test_rstring_ref_as_str__)
+            );
+        }
+
+        assert.rb_eq(lazy_eval("__test_rstring_ref_as_str__('❤️ ruby')"), "❤️ ruby".to_ruby());
+        assert.rb_eq(lazy_eval("__test_rstring_ref_as_str__('')"), "".to_ruby());
+        assert.rb_eq(lazy_eval("__test_rstring_ref_as_str__('ascii'.b)"), "ascii".to_ruby());
+        assert.rb_eq(lazy_eval("__test_rstring_ref_as_str__('ascii'.encode('US-ASCII'))"), "ascii".to_ruby());
+        assert.rb_eq(lazy_eval("__test_rstring_ref_as_str__('ascii'.encode('Shift_JIS'))"), "ascii".to_ruby());
+
+        let errors = [
+            ("\"\\xff\".force_encoding('UTF-8')", unsafe { rb_eArgError }),
+            ("\"\\xff\".b", unsafe { rb_eEncCompatError }),
+            ("'日本'.encode('Shift_JIS')", unsafe { rb_eEncCompatError }),
+            ("'ascii'.encode('UTF-16LE')", unsafe { rb_eEncCompatError }),
+            ("42", unsafe { rb_eTypeError }),
+        ];
+
+        for &(string, class) in &errors {
+            assert.rb_eq(
+                lazy_eval(&format!(r#"
+                    begin
+                    __test_rstring_ref_as_str__({})
+                    rescue Exception => e
+                    e.class
+                    end
+                "#, string)),
+                class
+            );
+        }
+
+        let value = "foo".to_ruby();
+
+        assert.rs_eq(value, unsafe { RStringRef::new(value) }.unwrap().value());
+    }
+}
@@ -1,5 +1,7 @@
-use libc::{c_char, c_int, c_uint, c_long, c_ulong, c_longlong, c_ulonglong, c_double, size_t, uintptr_t};
+use libc::{c_char, c_int, c_uint, c_long, c_ulong, c_longlong, c_ulonglong, c_double, c_void, size_t, uintptr_t};
 use std::mem::transmute;
+use std::slice;
+use intern::{rb_define_singleton_method, rb_define_private_method, rb_define_protected_method, rb_define_method_id};
 
 #[repr(transparent)]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -12,16 +14,36 @@ pub struct ID(uintptr_t);
 // TODO: explain why this is ok
 unsafe impl Sync for ID {}
 
+impl VALUE {
+    /// Smuggles a raw pointer through a `VALUE`, e.g. as the data argument of
+    /// a callback such as [`rb_protect`].
+    ///
+    /// The resulting `VALUE` is not a Ruby object and must never be handed
+    /// to Ruby as one.
+    #[inline(always)]
+    pub fn from_ptr<T>(ptr: *mut T) -> Self {
+        VALUE(ptr as uintptr_t)
+    }
+
+    /// Recovers a raw pointer previously smuggled with [`VALUE::from_ptr`].
+    #[inline(always)]
+    pub fn as_ptr<T>(self) -> *mut T {
+        self.0 as *mut T
+    }
+}
+
 #[repr(transparent)]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[allow(unpredictable_function_pointer_comparisons)]
 pub struct ANYARGS<T>(extern "C" fn() -> T);
 
 macro_rules! impl_from_arity {
     ($name:ident $(, $arg:ty)*) => {
         impl<T> ANYARGS<T> {
             #[inline(always)]
+            #[allow(clippy::useless_transmute)]
             pub fn $name(func: extern "C" fn($($arg),*) -> T) -> Self {
-                unsafe { ANYARGS(transmute(func)) }
+                unsafe { ANYARGS(transmute::<extern "C" fn($($arg),*) -> T, extern "C" fn() -> T>(func)) }
             }
         }
     }
@@ -43,6 +65,368 @@ impl_from_arity!(from_arity_12, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE,
 impl_from_arity!(from_arity_13, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE);
 impl_from_arity!(from_arity_14, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE);
 impl_from_arity!(from_arity_15, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE);
+impl_from_arity!(from_arity_16, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE);
+impl_from_arity!(from_argc_argv, c_int, *const VALUE, VALUE);
+impl_from_arity!(from_args_array, VALUE, VALUE);
+
+/// A method implementation paired with the arity Ruby will call it with.
+///
+/// [`rb_define_method`] and friends take the function and its arity as
+/// separate arguments, and a mismatch between the two is undefined behavior.
+/// Each constructor here only accepts a function with the signature matching
+/// its arity, and [`define_method`], [`define_singleton_method`] and
+/// [`define_module_function`] pass both along together.
+///
+/// * `arity_0` to `arity_15` - `VALUE func(VALUE obj, [VALUE arg, ]*)`
+/// * [`METHOD_FUNC::argc_argv`] - `VALUE func(int argc, VALUE *argv, VALUE obj)` (arity `-1`)
+/// * [`METHOD_FUNC::args_array`] - `VALUE func(VALUE obj, VALUE args)` (arity `-2`)
+#[derive(Copy, Clone, Debug)]
+#[allow(non_camel_case_types)]
+pub struct METHOD_FUNC {
+    func: ANYARGS<VALUE>,
+    arity: c_int,
+}
+
+impl METHOD_FUNC {
+    /// For methods taking any number of arguments, passed as a C array.
+    #[inline(always)]
+    pub fn argc_argv(func: extern "C" fn(c_int, *const VALUE, VALUE) -> VALUE) -> Self {
+        METHOD_FUNC { func: ANYARGS::from_argc_argv(func), arity: -1 }
+    }
+
+    /// For methods taking any number of arguments, passed as a Ruby [`Array`](rb_cArray).
+    #[inline(always)]
+    pub fn args_array(func: extern "C" fn(VALUE, VALUE) -> VALUE) -> Self {
+        METHOD_FUNC { func: ANYARGS::from_args_array(func), arity: -2 }
+    }
+
+    #[inline(always)]
+    pub fn func(&self) -> ANYARGS<VALUE> {
+        self.func
+    }
+
+    #[inline(always)]
+    pub fn arity(&self) -> c_int {
+        self.arity
+    }
+}
+
+macro_rules! impl_method_arity {
+    ($name:ident, $from:ident, $arity:expr $(, $arg:ty)*) => {
+        impl METHOD_FUNC {
+            #[inline(always)]
+            pub fn $name(func: extern "C" fn(VALUE $(, $arg)*) -> VALUE) -> Self {
+                METHOD_FUNC { func: ANYARGS::$from(func), arity: $arity }
+            }
+        }
+    }
+}
+
+impl_method_arity!(arity_0, from_arity_1, 0);
+impl_method_arity!(arity_1, from_arity_2, 1, VALUE);
+impl_method_arity!(arity_2, from_arity_3, 2, VALUE, VALUE);
+impl_method_arity!(arity_3, from_arity_4, 3, VALUE, VALUE, VALUE);
+impl_method_arity!(arity_4, from_arity_5, 4, VALUE, VALUE, VALUE, VALUE);
+impl_method_arity!(arity_5, from_arity_6, 5, VALUE, VALUE, VALUE, VALUE, VALUE);
+impl_method_arity!(arity_6, from_arity_7, 6, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE);
+impl_method_arity!(arity_7, from_arity_8, 7, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE);
+impl_method_arity!(arity_8, from_arity_9, 8, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE);
+impl_method_arity!(arity_9, from_arity_10, 9, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE);
+impl_method_arity!(arity_10, from_arity_11, 10, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE);
+impl_method_arity!(arity_11, from_arity_12, 11, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE);
+impl_method_arity!(arity_12, from_arity_13, 12, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE);
+impl_method_arity!(arity_13, from_arity_14, 13, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE);
+impl_method_arity!(arity_14, from_arity_15, 14, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE);
+impl_method_arity!(arity_15, from_arity_16, 15, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE);
+
+/// Defines a public method on a class.
+///
+/// The typed equivalent of [`rb_define_method`], see [`METHOD_FUNC`].
+///
+/// # Safety
+///
+/// * Undefined behavior if `name` string is not valid ASCII
+#[inline(always)]
+pub unsafe fn define_method(class: VALUE, name: *const c_char, method: METHOD_FUNC) {
+    rb_define_method(class, name, method.func, method.arity)
+}
+
+/// Defines a singleton method on a class.
+///
+/// The typed equivalent of [`rb_define_singleton_method`], see [`METHOD_FUNC`].
+///
+/// # Safety
+///
+/// See [`rb_define_singleton_method`](fn.rb_define_singleton_method.html#safety).
+#[inline(always)]
+pub unsafe fn define_singleton_method(class: VALUE, name: *const c_char, method: METHOD_FUNC) {
+    rb_define_singleton_method(class, name, method.func, method.arity)
+}
+
+/// Defines a module function on a module.
+///
+/// The typed equivalent of [`rb_define_module_function`], see [`METHOD_FUNC`].
+///
+/// # Safety
+///
+/// * Undefined behavior if `name` string is not valid ASCII
+#[inline(always)]
+pub unsafe fn define_module_function(module: VALUE, name: *const c_char, method: METHOD_FUNC) {
+    rb_define_module_function(module, name, method.func, method.arity)
+}
+
+/// Defines a private method on a class.
+///
+/// The typed equivalent of [`rb_define_private_method`], see [`METHOD_FUNC`].
+///
+/// # Safety
+///
+/// * Undefined behavior if `name` string is not valid ASCII
+#[inline(always)]
+pub unsafe fn define_private_method(class: VALUE, name: *const c_char, method: METHOD_FUNC) {
+    rb_define_private_method(class, name, method.func, method.arity)
+}
+
+/// Defines a protected method on a class.
+///
+/// The typed equivalent of [`rb_define_protected_method`], see [`METHOD_FUNC`].
+///
+/// # Safety
+///
+/// * Undefined behavior if `name` string is not valid ASCII
+#[inline(always)]
+pub unsafe fn define_protected_method(class: VALUE, name: *const c_char, method: METHOD_FUNC) {
+    rb_define_protected_method(class, name, method.func, method.arity)
+}
+
+/// Defines a public method on a class, with its name given as an `ID`.
+///
+/// The typed equivalent of [`rb_define_method_id`], see [`METHOD_FUNC`].
+///
+/// # Safety
+///
+/// No known issues.
+#[inline(always)]
+pub unsafe fn define_method_id(class: VALUE, mid: ID, method: METHOD_FUNC) {
+    rb_define_method_id(class, mid, method.func, method.arity)
+}
+
+macro_rules! impl_from_closure {
+    ($name:ident, $trampoline:ident $(, $arg:ident : $ty:ty)*) => {
+        extern "C" fn $trampoline<F: FnMut($($ty),*) -> T, T>(data: VALUE $(, $arg: $ty)*) -> T {
+            let closure = unsafe { &mut *data.as_ptr::<F>() };
+            closure($($arg),*)
+        }
+
+        impl<T> ANYARGS<T> {
+            /// Packages a Rust closure as a C callback and the data argument it
+            /// must be called with, e.g. for the `func`/`data` argument pairs
+            /// of [`rb_rescue`] and [`rb_ensure`].
+            ///
+            /// The callback is called with the returned `VALUE` as its first
+            /// argument, followed by the closure's own arguments.
+            ///
+            /// # Safety
+            ///
+            /// * The returned `VALUE` is a raw pointer to `closure` that isn't
+            /// tied to its lifetime. Undefined behavior if the callback is
+            /// called after `closure` is dropped or moved
+            /// * `closure` must not panic, as unwinding out of the callback
+            /// aborts the process
+            #[inline(always)]
+            pub unsafe fn $name<F: FnMut($($ty),*) -> T>(closure: &mut F) -> (Self, VALUE) {
+                let func = $trampoline::<F, T> as extern "C" fn(VALUE $(, $ty)*) -> T;
+                let func = transmute::<extern "C" fn(VALUE $(, $ty)*) -> T, extern "C" fn() -> T>(func);
+
+                (ANYARGS(func), VALUE::from_ptr(closure))
+            }
+        }
+    }
+}
+
+impl_from_closure!(from_closure_0, closure_trampoline_0);
+impl_from_closure!(from_closure_1, closure_trampoline_1, arg1: VALUE);
+
+/// Calls a method on an object, passing any number of arguments.
+///
+/// This wraps the variadic [`rb_funcall`](fn.rb_funcall.html), filling in the
+/// argument count.
+///
+/// ```ignore
+/// let padded = rb_funcall!(string, rb_intern(cstr!("center")), INT2NUM(7), "*".to_ruby());
+/// ```
+///
+/// # Safety
+///
+/// Must be used in an `unsafe` context. See
+/// [`rb_funcallv`](fn.rb_funcallv.html#safety).
+#[macro_export]
+macro_rules! rb_funcall {
+    ( $recv:expr, $mid:expr $(, $arg:expr)* ) => {
+        $crate::rb_funcall($recv, $mid, $crate::__rb_count_args!($(($arg))*) $(, $arg)*)
+    };
+}
+
+/// The callback type expected by [`rb_protect`], `VALUE (*)(VALUE)`.
+///
+/// Use [`PROTECT_FUNC::from_value`] when the argument is a Ruby object, or
+/// [`PROTECT_FUNC::from_ptr`] together with [`VALUE::from_ptr`] to pass
+/// arbitrary Rust data through.
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug)]
+#[allow(non_camel_case_types)]
+pub struct PROTECT_FUNC(extern "C" fn(VALUE) -> VALUE);
+
+impl PROTECT_FUNC {
+    #[inline(always)]
+    pub fn from_value(func: extern "C" fn(VALUE) -> VALUE) -> Self {
+        PROTECT_FUNC(func)
+    }
+
+    #[inline(always)]
+    pub fn from_ptr<T>(func: extern "C" fn(*mut T) -> VALUE) -> Self {
+        unsafe { PROTECT_FUNC(transmute::<extern "C" fn(*mut T) -> VALUE, extern "C" fn(VALUE) -> VALUE>(func)) }
+    }
+
+    /// Packages a Rust closure as a callback and the argument it must be
+    /// called with.
+    ///
+    /// # Safety
+    ///
+    /// * See [`ANYARGS::from_closure_0`]
+    #[inline(always)]
+    pub unsafe fn from_closure<F: FnMut() -> VALUE>(closure: &mut F) -> (Self, VALUE) {
+        (PROTECT_FUNC(closure_trampoline_0::<F, VALUE>), VALUE::from_ptr(closure))
+    }
+}
+
+/// The callback type expected by [`rb_block_call`], called each time the
+/// method yields to the block:
+/// `VALUE (*)(VALUE yielded_arg, VALUE callback_arg, int argc, const VALUE *argv, VALUE blockarg)`.
+///
+/// * `yielded_arg` - the first yielded value, or [`nil`](Qnil)
+/// * `callback_arg` - the `data2` argument given to [`rb_block_call`]
+/// * `argc` - the number of yielded values
+/// * `argv` - the yielded values, as a C array
+/// * `blockarg` - the block passed to the block, or [`nil`](Qnil)
+/// * Returns the value of the block
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug)]
+#[allow(non_camel_case_types)]
+pub struct BLOCK_CALL_FUNC(extern "C" fn(VALUE, VALUE, c_int, *const VALUE, VALUE) -> VALUE);
+
+extern "C" fn block_call_trampoline<F: FnMut(&[VALUE]) -> VALUE>(_yielded_arg: VALUE, callback_arg: VALUE, argc: c_int, argv: *const VALUE, _blockarg: VALUE) -> VALUE {
+    let closure = unsafe { &mut *callback_arg.as_ptr::<F>() };
+    let args = if argc > 0 { unsafe { slice::from_raw_parts(argv, argc as usize) } } else { &[] };
+    closure(args)
+}
+
+impl BLOCK_CALL_FUNC {
+    #[inline(always)]
+    pub fn new(func: extern "C" fn(VALUE, VALUE, c_int, *const VALUE, VALUE) -> VALUE) -> Self {
+        BLOCK_CALL_FUNC(func)
+    }
+
+    /// Packages a Rust closure as a block and the `data2` argument it must be
+    /// called with. The closure receives the yielded values.
+    ///
+    /// # Safety
+    ///
+    /// * See [`ANYARGS::from_closure_0`]
+    #[inline(always)]
+    pub unsafe fn from_closure<F: FnMut(&[VALUE]) -> VALUE>(closure: &mut F) -> (Self, VALUE) {
+        (BLOCK_CALL_FUNC(block_call_trampoline::<F>), VALUE::from_ptr(closure))
+    }
+}
+
+/// The allocator function type expected by [`rb_define_alloc_func`](fn.rb_define_alloc_func.html),
+/// `VALUE (*)(VALUE klass)`.
+///
+/// It receives the class being instantiated and returns a new, uninitialized
+/// instance of it, typically with [`rb_data_typed_object_wrap`] and a null
+/// data pointer, or with [`rb_data_typed_object_zalloc`]. It must not call
+/// into Ruby code, as `initialize` hasn't run yet.
+#[allow(non_camel_case_types)]
+pub type rb_alloc_func_t = extern "C" fn(VALUE) -> VALUE;
+
+/// The callbacks of an [`rb_data_type_t`].
+///
+/// * `dmark` - marks the Ruby objects referenced by the data, with
+/// `rb_gc_mark` and friends; `None` if there are none
+/// * `dfree` - frees the data; `None` to never free it
+/// * `dsize` - returns the memory used by the data, in bytes, for
+/// `ObjectSpace.memsize_of`; may be `None`
+/// * `dcompact` - updates the references marked as movable by `dmark` after
+/// `GC.compact` (Ruby 2.7+); must be `None` on older versions, where this
+/// slot is reserved
+/// * `reserved` - must be null
+///
+/// To let `GC.compact` move the objects referenced by the data, `dmark` marks
+/// them with `rb_gc_mark_movable` instead of `rb_gc_mark`, and `dcompact`
+/// replaces each of them with the result of `rb_gc_location`. Objects marked
+/// with `rb_gc_mark` are pinned in place.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+#[allow(non_camel_case_types)]
+pub struct rb_data_type_function_t {
+    pub dmark: Option<extern "C" fn(data: *mut c_void)>,
+    pub dfree: Option<extern "C" fn(data: *mut c_void)>,
+    pub dsize: Option<extern "C" fn(data: *const c_void) -> size_t>,
+    pub dcompact: Option<extern "C" fn(data: *mut c_void)>,
+    pub reserved: [*mut c_void; 1],
+}
+
+/// Describes a kind of Rust (or C) data that can be wrapped in a Ruby object
+/// with [`rb_data_typed_object_wrap`].
+///
+/// Objects of the same type are recognized by the address of their
+/// `rb_data_type_t`, so it is usually declared as a `static`:
+///
+/// ```ignore
+/// static FOO_TYPE: rb_data_type_t = rb_data_type_t {
+///     wrap_struct_name: cstr!("Foo"),
+///     function: rb_data_type_function_t {
+///         dmark: None,
+///         dfree: Some(foo_free),
+///         dsize: None,
+///         dcompact: None,
+///         reserved: [null_mut()],
+///     },
+///     parent: null(),
+///     data: null_mut(),
+///     flags: RUBY_TYPED_FREE_IMMEDIATELY,
+/// };
+/// ```
+///
+/// * `wrap_struct_name` - a nul-terminated name for the type, used in error
+/// messages and by `ObjectSpace`
+/// * `function` - see [`rb_data_type_function_t`]
+/// * `parent` - the type this one "inherits" from for [`rb_check_typeddata`],
+/// or null
+/// * `data` - arbitrary user data, ignored by Ruby
+/// * `flags` - e.g. [`RUBY_TYPED_FREE_IMMEDIATELY`]
+///
+/// # Ruby Documentation
+///
+/// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-C+struct+to+Ruby+object)
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+#[allow(non_camel_case_types)]
+pub struct rb_data_type_t {
+    pub wrap_struct_name: *const c_char,
+    pub function: rb_data_type_function_t,
+    pub parent: *const rb_data_type_t,
+    pub data: *mut c_void,
+    pub flags: VALUE,
+}
+
+// Types are meant to be immutable statics shared by all threads.
+unsafe impl Sync for rb_data_type_t {}
+
+/// An [`rb_data_type_t`] flag indicating that `dfree` can be called
+/// immediately when the object is collected, rather than deferred to a
+/// finalizer. `dfree` must then not block or touch other Ruby objects.
+pub const RUBY_TYPED_FREE_IMMEDIATELY: VALUE = VALUE(1);
 
 /// Data types used within the Ruby C interpreter
 ///
@@ -84,7 +468,7 @@ impl_from_arity!(from_arity_15, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE, VALUE,
 #[allow(non_camel_case_types)]
 pub struct ruby_value_type(c_int);
 
-extern {
+extern "C" {
     /// This type denotes an ordinary Ruby object.
     #[link_name = "RS_T_OBJECT"]
     pub static T_OBJECT: ruby_value_type;
@@ -207,6 +591,14 @@ extern {
     #[link_name = "RS_Qnil"]
     pub static Qnil: VALUE;
 
+    /// A special value meaning "no value given", distinct from [`nil`](Qnil).
+    ///
+    /// Returned by some APIs, such as [`rb_get_kwargs`], in place of an
+    /// argument that was not passed. It is not a Ruby object and must never be
+    /// handed to Ruby code.
+    #[link_name = "RS_Qundef"]
+    pub static Qundef: VALUE;
+
 
     /// The `Kernel` module
     ///
@@ -995,6 +1387,41 @@ extern {
     #[link_name = "RS_RSTRING_PTR"]
     pub fn RSTRING_PTR(string: VALUE) -> *const c_char;
 
+    /// Returns a mutable pointer to the Ruby [`String`](rb_cString) data, for
+    /// filling it in place.
+    ///
+    /// ```ignore
+    /// rb_str_modify_expand(string, len);
+    ///
+    /// let ptr = RSTRING_PTR_MUT(string).add(RSTRING_LEN(string) as usize);
+    /// let written = read(fd, ptr as *mut c_void, len as size_t);
+    ///
+    /// rb_str_set_len(string, RSTRING_LEN(string) + written as c_long);
+    /// ```
+    ///
+    /// * `string` - an instance of [`String`](rb_cString)
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `string` is not a `String`
+    /// * `string` must first be made mutable and independent of other strings
+    /// with [`rb_str_modify`](fn.rb_str_modify.html) or
+    /// [`rb_str_modify_expand`](fn.rb_str_modify_expand.html); otherwise
+    /// writes may be visible through shared or frozen strings
+    /// * Only the first `capacity` bytes may be written (see
+    /// [`rb_str_modify_expand`](fn.rb_str_modify_expand.html)), and the length
+    /// must then be updated with [`rb_str_set_len`](fn.rb_str_set_len.html)
+    /// * The pointer is invalidated by any call that may resize or share the
+    /// string, and the string must be kept alive while it is in use (see
+    /// [`gc_guard`](fn.gc_guard.html))
+    ///
+    /// # Miscellaneous
+    ///
+    /// * Not a Ruby macro: a mutable version of [`RSTRING_PTR`], defined in
+    /// `ext/ruby.c`.
+    #[link_name = "RS_RSTRING_PTR_MUT"]
+    pub fn RSTRING_PTR_MUT(string: VALUE) -> *mut c_char;
+
     /// Returns the number of elements in the Ruby [`Array`](rb_cArray).
     ///
     /// * `array` - an instance of [`Array`](rb_cArray)
@@ -1019,6 +1446,46 @@ extern {
     #[link_name = "RS_RHASH_SIZE"]
     pub fn RHASH_SIZE(hash: VALUE) -> size_t;
 
+    /// Returns a C boolean (zero if false, non-zero if true) indicating
+    /// whether a [`T_DATA`] object was created with an [`rb_data_type_t`].
+    ///
+    /// * `obj` - a `T_DATA` object
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `obj` is not a `T_DATA` object
+    ///
+    //+ c-macro: `#define RTYPEDDATA_P(v)`
+    #[link_name = "RS_RTYPEDDATA_P"]
+    pub fn RTYPEDDATA_P(obj: VALUE) -> c_int;
+
+    /// Returns the [`rb_data_type_t`] of a typed data object.
+    ///
+    /// * `obj` - a typed data object
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `obj` is not a typed data object, see [`RTYPEDDATA_P`]
+    ///
+    //+ c-macro: `#define RTYPEDDATA_TYPE(v)`
+    #[link_name = "RS_RTYPEDDATA_TYPE"]
+    pub fn RTYPEDDATA_TYPE(obj: VALUE) -> *const rb_data_type_t;
+
+    /// Returns the data pointer wrapped by a typed data object, without
+    /// checking its type.
+    ///
+    /// * `obj` - a typed data object
+    ///
+    /// Prefer [`rb_check_typeddata`], which also checks the type.
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `obj` is not a typed data object, see [`RTYPEDDATA_P`]
+    ///
+    //+ c-macro: `#define RTYPEDDATA_DATA(v)`
+    #[link_name = "RS_RTYPEDDATA_DATA"]
+    pub fn RTYPEDDATA_DATA(obj: VALUE) -> *mut c_void;
+
     /// Converts an ASCII-encoded, nul-terminated C string to an [`ID`].
     ///
     /// * `cstr` - nul-terminated C string
@@ -1134,6 +1601,85 @@ extern {
     //+ c-func: class.c `VALUE rb_define_class(const char*,VALUE)`
     pub fn rb_define_class(name: *const c_char, superclass: VALUE) -> VALUE;
 
+    /// Defines a new class under a namespace.
+    ///
+    /// NOTE: If the class is already defined and the superclass is the same
+    /// as specified, it will return the already defined class.
+    ///
+    /// * `outer` - the namespace, a [`Class`](rb_cClass) or [`Module`](rb_cModule)
+    /// * `name` - an ASCII-encoded, nul-terminated C string
+    /// * `superclass` - [`Class`](rb_cClass)
+    /// * Returns a [`Class`](rb_cClass)
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `name` string is not valid ASCII.
+    ///
+    /// ## Exceptions
+    ///
+    /// * See [`rb_define_class`](fn.rb_define_class.html#exceptions)
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * **2.5:**
+    ///     [usage](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Class+and+Module+Definition),
+    ///     [spec](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Defining+Classes+and+Modules)
+    ///
+    //+ c-func: class.c `VALUE rb_define_class_under(VALUE, const char*, VALUE)`
+    pub fn rb_define_class_under(outer: VALUE, name: *const c_char, superclass: VALUE) -> VALUE;
+
+    /// Defines a new module.
+    ///
+    /// NOTE: If the module is already defined, it will return the already
+    /// defined module.
+    ///
+    /// * `name` - an ASCII-encoded, nul-terminated C string
+    /// * Returns a [`Module`](rb_cModule)
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `name` string is not valid ASCII.
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if the constant name is already taken, but the constant is not a module
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * **2.5:**
+    ///     [usage](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Class+and+Module+Definition),
+    ///     [spec](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Defining+Classes+and+Modules)
+    ///
+    //+ c-func: class.c `VALUE rb_define_module(const char*)`
+    pub fn rb_define_module(name: *const c_char) -> VALUE;
+
+    /// Defines a new module under a namespace.
+    ///
+    /// NOTE: If the module is already defined, it will return the already
+    /// defined module.
+    ///
+    /// * `outer` - the namespace, a [`Class`](rb_cClass) or [`Module`](rb_cModule)
+    /// * `name` - an ASCII-encoded, nul-terminated C string
+    /// * Returns a [`Module`](rb_cModule)
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `name` string is not valid ASCII.
+    ///
+    /// ## Exceptions
+    ///
+    /// * See [`rb_define_module`](fn.rb_define_module.html#exceptions)
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * **2.5:**
+    ///     [usage](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Class+and+Module+Definition),
+    ///     [spec](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Defining+Classes+and+Modules)
+    ///
+    //+ c-func: class.c `VALUE rb_define_module_under(VALUE, const char*)`
+    pub fn rb_define_module_under(outer: VALUE, name: *const c_char) -> VALUE;
+
     /// Defines a public method on a class.
     ///
     /// * `class` - a [`Class`](rb_cClass)
@@ -1144,10 +1690,15 @@ extern {
     ///     * if `-1`, function will be called as: `VALUE func(int argc, VALUE *argv, VALUE obj)`
     ///     * if `-2`, function will be called as: `VALUE func(VALUE obj, VALUE args)`
     ///
+    /// [`define_method`] takes the function and arity as a single
+    /// [`METHOD_FUNC`], which rules out a mismatch between them.
+    ///
     /// # Safety
     ///
     /// * Undefined behavior if `name` string is not valid ASCII
     /// * Undefined behavior if `func` signature doesn't match `arity`
+    /// * A panic unwinding out of `func` aborts the process; define `func` with
+    /// [`rb_method!`](macro.rb_method.html) to raise it as a Ruby exception instead.
     ///
     /// ## Exceptions
     ///
@@ -1193,826 +1744,2441 @@ extern {
     //+ c-func: class.c `void rb_undef_method(VALUE,const char*)`
     pub fn rb_undef_method(class: VALUE, name: *const c_char);
 
-    /// Gets the object's class' name
+    /// Defines an alias for a method on a class.
     ///
-    /// * `obj` - any Ruby object
+    /// This is the equivalent of `alias_method :new_name, :old_name`.
+    ///
+    /// * `class` - a [`Class`](rb_cClass) or [`Module`](rb_cModule)
+    /// * `new_name` - the name of the alias, an ASCII-encoded, nul-terminated C string
+    /// * `old_name` - the name of the existing method, an ASCII-encoded, nul-terminated C string
     ///
     /// # Safety
     ///
-    /// No known issues
+    /// * Undefined behavior if `new_name` or `old_name` is not valid ASCII
     ///
-    //+ c-func: variable.c `const char *rb_obj_classname(VALUE)`
-    pub fn rb_obj_classname(obj: VALUE) -> *const c_char;
+    /// ## Exceptions
+    ///
+    /// * [`NameError`](rb_eNameError)
+    ///     * if the method `old_name` is not defined
+    ///
+    //+ c-func: class.c `void rb_define_alias(VALUE,const char*,const char*)`
+    pub fn rb_define_alias(class: VALUE, new_name: *const c_char, old_name: *const c_char);
 
-    /// Returns a C boolean (zero if false, non-zero if true) indicated whether yield would
-    /// execute a block in the current method.
+    /// Defines attribute accessors on a class for the instance variable of the
+    /// same name (prefixed with `@`).
     ///
-    /// This is the equivalent of `Kernel#block_given?`.
+    /// This is the equivalent of `attr_reader`, `attr_writer` or `attr_accessor`.
+    ///
+    /// * `class` - a [`Class`](rb_cClass) or [`Module`](rb_cModule)
+    /// * `name` - an ASCII-encoded, nul-terminated C string
+    /// * `read` - a C boolean, whether to define the reader `name`
+    /// * `write` - a C boolean, whether to define the writer `name=`
     ///
     /// # Safety
     ///
-    /// No known issues
+    /// * Undefined behavior if `name` string is not valid ASCII
     ///
-    //+ c-func: eval.c `int rb_block_given_p(void)`
-    pub fn rb_block_given_p() -> c_int;
-}
-
-tests! {
-    use super::*;
-    use super::super::intern;
-    use super::super::testing::{Assertions, ToRuby, lazy_eval, c_bool};
-    use std::ptr::null;
+    /// ## Exceptions
+    ///
+    /// * [`NameError`](rb_eNameError)
+    ///     * if `name` is not a valid attribute name
+    ///
+    //+ c-func: class.c `void rb_define_attr(VALUE,const char*,int,int)`
+    pub fn rb_define_attr(class: VALUE, name: *const c_char, read: c_int, write: c_int);
 
-    use std::ffi::{CStr, CString};
+    /// Includes a module into a class or module.
+    ///
+    /// This is the equivalent of `include`. For example, a class implementing
+    /// `<=>` can include [`Comparable`](rb_mComparable), and one implementing
+    /// `each` can include [`Enumerable`](rb_mEnumerable).
+    ///
+    /// * `class` - a [`Class`](rb_cClass) or [`Module`](rb_cModule)
+    /// * `module` - the [`Module`](rb_cModule) to include
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if `module` is not a module
+    /// * [`ArgumentError`](rb_eArgError)
+    ///     * if the inclusion would be cyclic
+    ///
+    //+ c-func: class.c `void rb_include_module(VALUE,VALUE)`
+    pub fn rb_include_module(class: VALUE, module: VALUE);
 
-    #[test]
-    fn test_false(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("false"), unsafe { Qfalse });
+    /// Extends an object with a module, i.e. includes the module into the
+    /// object's singleton class.
+    ///
+    /// This is the equivalent of `extend`, without calling the `extended`
+    /// hook.
+    ///
+    /// * `obj` - any Ruby object that can have a singleton class
+    /// * `module` - the [`Module`](rb_cModule) to include
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if `module` is not a module, or `obj` can't have a singleton
+    ///     class (e.g. an `Integer` or a `Symbol`)
+    ///
+    //+ c-func: eval.c `void rb_extend_object(VALUE,VALUE)`
+    pub fn rb_extend_object(obj: VALUE, module: VALUE);
+
+    /// Prepends a module to a class or module, so that its methods take
+    /// precedence over those defined by the class itself.
+    ///
+    /// This is the equivalent of `prepend`.
+    ///
+    /// * `class` - a [`Class`](rb_cClass) or [`Module`](rb_cModule)
+    /// * `module` - the [`Module`](rb_cModule) to prepend
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if `module` is not a module
+    /// * [`ArgumentError`](rb_eArgError)
+    ///     * if the prepending would be cyclic
+    ///
+    //+ c-func: class.c `void rb_prepend_module(VALUE,VALUE)`
+    pub fn rb_prepend_module(class: VALUE, module: VALUE);
+
+    /// Gets the object's class' name
+    ///
+    /// * `obj` - any Ruby object
+    ///
+    /// # Safety
+    ///
+    /// No known issues
+    ///
+    //+ c-func: variable.c `const char *rb_obj_classname(VALUE)`
+    pub fn rb_obj_classname(obj: VALUE) -> *const c_char;
+
+    /// Returns a C boolean (zero if false, non-zero if true) indicated whether yield would
+    /// execute a block in the current method.
+    ///
+    /// This is the equivalent of `Kernel#block_given?`.
+    ///
+    /// # Safety
+    ///
+    /// No known issues
+    ///
+    //+ c-func: eval.c `int rb_block_given_p(void)`
+    pub fn rb_block_given_p() -> c_int;
+
+    /// Raises an exception of the given class with a formatted message.
+    ///
+    /// This function never returns; control is transferred to the nearest
+    /// `rescue` (or equivalent) on the Ruby side.
+    ///
+    /// * `class` - an exception class, such as [`rb_eArgError`]
+    /// * `fmt` - a nul-terminated, `printf`-style format string
+    /// * `...` - arguments for the format string
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `fmt` is not a nul-terminated C string
+    /// * Undefined behavior if the variadic arguments don't match `fmt`
+    /// * The call unwinds via `longjmp`, so destructors of any Rust values
+    /// still on the stack will not run. Drop them before raising.
+    ///
+    /// # Miscellaneous
+    ///
+    /// * The message is formatted with Ruby's own `printf` implementation,
+    /// so a literal `%` must be written as `%%`. When the message comes from
+    /// user input, pass it as an argument to `"%s"` instead.
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Exceptions+and+Errors)
+    ///
+    //+ c-func: error.c `void rb_raise(VALUE, const char*, ...)`
+    pub fn rb_raise(class: VALUE, fmt: *const c_char, ...) -> !;
+
+    /// Calls `func` with `arg`, catching any exception (or other non-local
+    /// jump, such as `throw` or `break`) raised while it runs.
+    ///
+    /// * `func` - a [`PROTECT_FUNC`]
+    /// * `arg` - passed through to `func`
+    /// * `state` - set to `0` if `func` returned normally; otherwise set to a
+    /// non-zero tag identifying the kind of jump that was intercepted
+    /// * Returns the result of `func`, or [`nil`](Qnil) if `state` is non-zero
+    ///
+    /// When an exception was raised, it can be retrieved with [`rb_errinfo`].
+    /// The jump can be resumed later with [`rb_jump_tag`], or discarded by
+    /// clearing the error with [`rb_set_errinfo`].
+    ///
+    /// # Safety
+    ///
+    /// * `state` must be a valid pointer to a C int
+    /// * Rust frames between this call and the raise point (i.e. inside
+    /// `func`) are still unwound via `longjmp`, so `func` itself must not
+    /// hold values with destructors across calls into Ruby.
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Exceptions+and+Errors)
+    ///
+    //+ c-func: eval.c `VALUE rb_protect(VALUE (*)(VALUE), VALUE, int*)`
+    pub fn rb_protect(func: PROTECT_FUNC, arg: VALUE, state: *mut c_int) -> VALUE;
+
+    /// Resumes a non-local jump previously intercepted by [`rb_protect`].
+    ///
+    /// This function never returns.
+    ///
+    /// * `state` - the non-zero tag set by [`rb_protect`]
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `state` is not a tag produced by [`rb_protect`]
+    /// * The call unwinds via `longjmp`, so destructors of any Rust values
+    /// still on the stack will not run. Drop them before jumping.
+    ///
+    //+ c-func: eval.c `void rb_jump_tag(int)`
+    pub fn rb_jump_tag(state: c_int) -> !;
+
+    /// Returns the exception currently being handled (Ruby's `$!`), or
+    /// [`nil`](Qnil) if there is none.
+    ///
+    /// # Safety
+    ///
+    /// No known issues.
+    ///
+    //+ c-func: eval.c `VALUE rb_errinfo(void)`
+    pub fn rb_errinfo() -> VALUE;
+
+    /// Sets the exception currently being handled (Ruby's `$!`).
+    ///
+    /// Pass [`nil`](Qnil) to clear it after an exception caught by
+    /// [`rb_protect`] has been handled.
+    ///
+    /// * `err` - an [`Exception`](rb_eException) or [`nil`](Qnil)
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if `err` is neither `nil` nor an exception object
+    ///
+    //+ c-func: eval.c `void rb_set_errinfo(VALUE)`
+    pub fn rb_set_errinfo(err: VALUE);
+
+    /// Calls `b_proc` with `data1`, and if a [`StandardError`](rb_eStandardError)
+    /// is raised, calls `r_proc` with `data2` and the exception.
+    ///
+    /// This is the equivalent of Ruby's `begin`/`rescue`.
+    ///
+    /// * `b_proc` - `VALUE b_proc(VALUE data1)`
+    /// * `data1` - passed through to `b_proc`
+    /// * `r_proc` - `VALUE r_proc(VALUE data2, VALUE exception)`
+    /// * `data2` - passed through to `r_proc`
+    /// * Returns the result of `b_proc`, or of `r_proc` if an exception was rescued
+    ///
+    /// [`ANYARGS::from_closure_0`] and [`ANYARGS::from_closure_1`] build
+    /// suitable callback and data pairs from Rust closures.
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `b_proc` or `r_proc` signatures don't match the above
+    /// * Rust frames inside `b_proc` are unwound via `longjmp` when it raises,
+    /// so their destructors will not run.
+    ///
+    /// ## Exceptions
+    ///
+    /// * Exceptions that are not a `StandardError` are propagated
+    /// * Exceptions raised by `r_proc` are propagated
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Exceptions+and+Errors)
+    ///
+    //+ c-func: eval.c `VALUE rb_rescue(VALUE(*)(ANYARGS),VALUE,VALUE(*)(ANYARGS),VALUE)`
+    pub fn rb_rescue(b_proc: ANYARGS<VALUE>, data1: VALUE, r_proc: ANYARGS<VALUE>, data2: VALUE) -> VALUE;
+
+    /// Like [`rb_rescue`], but only rescues exceptions that are instances of
+    /// the given classes.
+    ///
+    /// This is the equivalent of Ruby's `begin`/`rescue ClassA, ClassB`.
+    ///
+    /// * `b_proc` - `VALUE b_proc(VALUE data1)`
+    /// * `data1` - passed through to `b_proc`
+    /// * `r_proc` - `VALUE r_proc(VALUE data2, VALUE exception)`
+    /// * `data2` - passed through to `r_proc`
+    /// * `...` - exception classes to rescue, terminated by [`Qfalse`]
+    /// (i.e. `(VALUE)0`)
+    /// * Returns the result of `b_proc`, or of `r_proc` if an exception was rescued
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if the list of classes is not terminated by `Qfalse`
+    /// * See also [`rb_rescue`](fn.rb_rescue.html#safety)
+    ///
+    /// ## Exceptions
+    ///
+    /// * Exceptions not matching any of the given classes are propagated
+    /// * Exceptions raised by `r_proc` are propagated
+    ///
+    //+ c-func: eval.c `VALUE rb_rescue2(VALUE(*)(ANYARGS),VALUE,VALUE(*)(ANYARGS),VALUE,...)`
+    pub fn rb_rescue2(b_proc: ANYARGS<VALUE>, data1: VALUE, r_proc: ANYARGS<VALUE>, data2: VALUE, ...) -> VALUE;
+
+    /// Calls `b_proc` with `data1`, then calls `e_proc` with `data2`, even if
+    /// `b_proc` raised an exception or otherwise jumped out.
+    ///
+    /// This is the equivalent of Ruby's `begin`/`ensure`.
+    ///
+    /// * `b_proc` - `VALUE b_proc(VALUE data1)`
+    /// * `data1` - passed through to `b_proc`
+    /// * `e_proc` - `VALUE e_proc(VALUE data2)`, its return value is ignored
+    /// * `data2` - passed through to `e_proc`
+    /// * Returns the result of `b_proc`
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `b_proc` or `e_proc` signatures don't match the above
+    /// * Rust frames inside `b_proc` are unwound via `longjmp` when it raises,
+    /// so their destructors will not run.
+    ///
+    /// ## Exceptions
+    ///
+    /// * Exceptions raised by `b_proc` are propagated once `e_proc` has run
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Exceptions+and+Errors)
+    ///
+    //+ c-func: eval.c `VALUE rb_ensure(VALUE(*)(ANYARGS),VALUE,VALUE(*)(ANYARGS),VALUE)`
+    pub fn rb_ensure(b_proc: ANYARGS<VALUE>, data1: VALUE, e_proc: ANYARGS<VALUE>, data2: VALUE) -> VALUE;
+
+    /// Calls a method on an object, passing any number of arguments.
+    ///
+    /// Prefer the [`rb_funcall!`](macro.rb_funcall.html) macro, which fills
+    /// in `argc` for you.
+    ///
+    /// * `recv` - the receiver, any Ruby object
+    /// * `mid` - the `ID` of the method name
+    /// * `argc` - the number of arguments that follow
+    /// * `...` - the arguments, each a `VALUE`
+    /// * Returns the result of the method call
+    ///
+    /// # Safety
+    ///
+    /// * Undefined behavior if `argc` doesn't match the number of arguments
+    /// * See also [`rb_funcallv`](fn.rb_funcallv.html#safety)
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Invoking+Ruby+method+from+C)
+    ///
+    //+ c-func: vm_eval.c `VALUE rb_funcall(VALUE, ID, int, ...)`
+    pub fn rb_funcall(recv: VALUE, mid: ID, argc: c_int, ...) -> VALUE;
+
+    /// Calls a method on an object, passing the arguments as a C array.
+    ///
+    /// Like Ruby's `Object#send`, private and protected methods can be called.
+    ///
+    /// * `recv` - the receiver, any Ruby object
+    /// * `mid` - the `ID` of the method name
+    /// * `argc` - number of arguments passed
+    /// * `argv` - pointer to the arguments, passed as a C array
+    /// * Returns the result of the method call
+    ///
+    /// # Safety
+    ///
+    /// * `argv` must point to a location in memory containing at least `argc` number
+    /// of Ruby objects, (i.e. a valid C `VALUE` array of at least size `argc`)
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`NoMethodError`](rb_eNoMethodError)
+    ///     * if `recv` does not respond to the method
+    /// * [`ArgumentError`](rb_eArgError)
+    ///     * if `argc` doesn't match the arity of the method
+    /// * The method itself may raise any exception
+    ///
+    /// # Miscellaneous
+    ///
+    /// [`rb_funcall2`](https://github.com/ruby/ruby/blob/v2_5_1/include/ruby/ruby.h#L1789)
+    /// is currently an alias for this.
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Invoking+Ruby+method+from+C)
+    ///
+    //+ c-func: vm_eval.c `VALUE rb_funcallv(VALUE, ID, int, const VALUE*)`
+    pub fn rb_funcallv(recv: VALUE, mid: ID, argc: c_int, argv: *const VALUE) -> VALUE;
+
+    /// Calls a public method on an object, passing the arguments as a C array.
+    ///
+    /// Like Ruby's `Object#public_send`, calling a private or protected method
+    /// raises [`NoMethodError`](rb_eNoMethodError).
+    ///
+    /// See [`rb_funcallv`](rb_funcallv) for details on arguments.
+    ///
+    /// # Safety
+    ///
+    /// See [`rb_funcallv`](fn.rb_funcallv.html#safety).
+    ///
+    /// # Miscellaneous
+    ///
+    /// [`rb_funcall3`](https://github.com/ruby/ruby/blob/v2_5_1/include/ruby/ruby.h#L1790)
+    /// is currently an alias for this.
+    ///
+    //+ c-func: vm_eval.c `VALUE rb_funcallv_public(VALUE, ID, int, const VALUE*)`
+    pub fn rb_funcallv_public(recv: VALUE, mid: ID, argc: c_int, argv: *const VALUE) -> VALUE;
+
+    /// Calls a public method on an object, passing along the block given to
+    /// the current method (if any).
+    ///
+    /// See [`rb_funcallv`](rb_funcallv) for details on arguments.
+    ///
+    /// # Safety
+    ///
+    /// See [`rb_funcallv`](fn.rb_funcallv.html#safety).
+    ///
+    //+ c-func: vm_eval.c `VALUE rb_funcall_passing_block(VALUE, ID, int, const VALUE*)`
+    pub fn rb_funcall_passing_block(recv: VALUE, mid: ID, argc: c_int, argv: *const VALUE) -> VALUE;
+
+    /// Calls a public method on an object with the given block.
+    ///
+    /// * `block` - a [`Proc`](rb_cProc) (or any object responding to `to_proc`),
+    /// or [`nil`](Qnil) to call without a block
+    ///
+    /// See [`rb_funcallv`](rb_funcallv) for details on the other arguments.
+    ///
+    /// # Safety
+    ///
+    /// See [`rb_funcallv`](fn.rb_funcallv.html#safety).
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if `block` cannot be converted to a `Proc`
+    ///
+    //+ c-func: vm_eval.c `VALUE rb_funcall_with_block(VALUE, ID, int, const VALUE*, VALUE)`
+    pub fn rb_funcall_with_block(recv: VALUE, mid: ID, argc: c_int, argv: *const VALUE, block: VALUE) -> VALUE;
+
+    /// Parses the arguments of a method defined with arity `-1` according to
+    /// a format string, storing them through the given pointers.
+    ///
+    /// The format is made of, in order and all optional:
+    ///
+    /// * a digit - the number of leading mandatory arguments
+    /// * a digit - the number of optional arguments
+    /// * `*` - the remaining arguments, collected into an [`Array`](rb_cArray)
+    /// * a digit - the number of trailing mandatory arguments
+    /// * `:` - a trailing keyword [`Hash`](rb_cHash)
+    /// * `&` - the block, as a [`Proc`](rb_cProc)
+    ///
+    /// * `argc` - the `argc` passed to the method
+    /// * `argv` - the `argv` passed to the method
+    /// * `fmt` - a nul-terminated format string, e.g. `"12*:&"`
+    /// * `...` - one `*mut VALUE` per argument described by `fmt`; arguments
+    /// that were not given are set to [`nil`](Qnil)
+    /// * Returns `argc`
+    ///
+    /// [`scan_args`](fn.scan_args.html) is a Rust alternative that doesn't need variadic
+    /// arguments.
+    ///
+    /// # Safety
+    ///
+    /// * `argv` must point to at least `argc` `VALUE`s
+    /// * `fmt` must be a nul-terminated C string
+    /// * Undefined behavior if the number of pointers doesn't match `fmt`, or
+    /// if any of them is not a valid pointer to a `VALUE`
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`ArgumentError`](rb_eArgError)
+    ///     * if the number of arguments doesn't match `fmt`
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Method+and+singleton+method+definition)
+    ///
+    //+ c-func: class.c `int rb_scan_args(int, const VALUE*, const char*, ...)`
+    pub fn rb_scan_args(argc: c_int, argv: *const VALUE, fmt: *const c_char, ...) -> c_int;
+
+    /// Retrieves keyword arguments from a keyword [`Hash`](rb_cHash).
+    ///
+    /// * `keyword_hash` - the keyword hash, e.g. as parsed by [`rb_scan_args`]
+    /// with a `:` in its format, or [`nil`](Qnil)
+    /// * `table` - an array of `required + optional` keyword `ID`s, required
+    /// keywords first
+    /// * `required` - the number of required keywords
+    /// * `optional` - the number of optional keywords; if negative, its
+    /// absolute value minus one is used, and unknown keywords are allowed
+    /// * `values` - an array of at least `required + optional` `VALUE`s to
+    /// store the values in, or null to only check the keywords; optional
+    /// keywords that were not given are set to [`Qundef`]
+    /// * Returns the number of keywords found
+    ///
+    /// When `values` is not null, found keywords are removed from `keyword_hash`.
+    ///
+    /// # Safety
+    ///
+    /// * `table` must point to at least `required + optional` `ID`s
+    /// * `values` must be null or point to at least `required + optional` `VALUE`s
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`ArgumentError`](rb_eArgError)
+    ///     * if a required keyword is missing
+    ///     * if an unknown keyword is given and `optional` is not negative
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Method+and+singleton+method+definition)
+    ///
+    //+ c-func: class.c `int rb_get_kwargs(VALUE keyword_hash, const ID *table, int required, int optional, VALUE *)`
+    pub fn rb_get_kwargs(keyword_hash: VALUE, table: *const ID, required: c_int, optional: c_int, values: *mut VALUE) -> c_int;
+
+    /// Splits a [`Hash`](rb_cHash) into its `Symbol` keys and the rest.
+    ///
+    /// * `orighash` - a pointer to a hash; on return it points to a new hash
+    /// with the non-`Symbol` keys, or to `0` if there are none
+    /// * Returns a new hash with the `Symbol` keys, or `0` if there are none
+    ///
+    /// An empty hash is returned as is, rather than `0`, and `orighash` is set
+    /// to `0`. A `0` result is the same as [`Qfalse`].
+    ///
+    /// # Safety
+    ///
+    /// * `orighash` must be a valid pointer to a `Hash`
+    ///
+    //+ c-func: class.c `VALUE rb_extract_keywords(VALUE *orighash)`
+    pub fn rb_extract_keywords(orighash: *mut VALUE) -> VALUE;
+
+    /// Returns a C boolean (zero if false, non-zero if true) indicating
+    /// whether the current method was called with keyword arguments.
+    ///
+    /// Only available with the `ruby_2_7` feature, as it was introduced in
+    /// Ruby 2.7.
+    ///
+    /// # Safety
+    ///
+    /// No known issues.
+    ///
+    /// # Miscellaneous
+    ///
+    /// * Defined in `eval.c` and declared in `ruby/ruby.h` since 2.7.
+    #[cfg(feature = "ruby_2_7")]
+    pub fn rb_keyword_given_p() -> c_int;
+
+    /// Raises a [`LocalJumpError`](rb_eLocalJumpError) unless a block was
+    /// given to the current method.
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`LocalJumpError`](rb_eLocalJumpError)
+    ///     * if no block was given
+    ///
+    //+ c-func: eval.c `void rb_need_block(void)`
+    pub fn rb_need_block();
+
+    /// Yields a value to the block given to the current method.
+    ///
+    /// This is the equivalent of `yield value`.
+    ///
+    /// * `value` - the value to yield
+    /// * Returns the value of the block
+    ///
+    /// # Safety
+    ///
+    /// * Rust frames are unwound via `longjmp` if the block raises, breaks or
+    /// otherwise jumps out, so their destructors will not run.
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`LocalJumpError`](rb_eLocalJumpError)
+    ///     * if no block was given
+    /// * The block itself may raise any exception
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Control+Structure)
+    ///
+    //+ c-func: vm_eval.c `VALUE rb_yield(VALUE)`
+    pub fn rb_yield(value: VALUE) -> VALUE;
+
+    /// Yields several values to the block given to the current method.
+    ///
+    /// This is the equivalent of `yield a, b, c`.
+    ///
+    /// * `n` - the number of values
+    /// * `argv` - the values, as a C array
+    /// * Returns the value of the block
+    ///
+    /// # Safety
+    ///
+    /// * `argv` must point to at least `n` `VALUE`s
+    /// * See also [`rb_yield`](fn.rb_yield.html#safety)
+    ///
+    //+ c-func: vm_eval.c `VALUE rb_yield_values2(int n, const VALUE *argv)`
+    pub fn rb_yield_values2(n: c_int, argv: *const VALUE) -> VALUE;
+
+    /// Yields the elements of an array as separate values to the block given
+    /// to the current method.
+    ///
+    /// This is the equivalent of `yield *values`.
+    ///
+    /// * `values` - an [`Array`](rb_cArray)
+    /// * Returns the value of the block
+    ///
+    /// # Safety
+    ///
+    /// * See [`rb_yield`](fn.rb_yield.html#safety)
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`ArgumentError`](rb_eArgError)
+    ///     * if `values` is not an `Array`
+    ///
+    //+ c-func: vm_eval.c `VALUE rb_yield_splat(VALUE)`
+    pub fn rb_yield_splat(values: VALUE) -> VALUE;
+
+    /// Calls a method on an object with a C function as its block.
+    ///
+    /// This is the equivalent of `recv.mid(*argv) { |*args| ... }`.
+    ///
+    /// * `recv` - the receiver, any Ruby object
+    /// * `mid` - the `ID` of the method name
+    /// * `argc` - number of arguments passed
+    /// * `argv` - pointer to the arguments, passed as a C array
+    /// * `bl_proc` - a [`BLOCK_CALL_FUNC`], called each time the method yields
+    /// * `data2` - passed through to `bl_proc` as its `callback_arg`
+    /// * Returns the result of the method call
+    ///
+    /// [`BLOCK_CALL_FUNC::from_closure`] builds a suitable callback and data
+    /// pair from a Rust closure.
+    ///
+    /// # Safety
+    ///
+    /// * See [`rb_funcallv`](fn.rb_funcallv.html#safety)
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Control+Structure)
+    ///
+    //+ c-func: vm_eval.c `VALUE rb_block_call(VALUE,ID,int,const VALUE*,rb_block_call_func_t,VALUE)`
+    pub fn rb_block_call(recv: VALUE, mid: ID, argc: c_int, argv: *const VALUE, bl_proc: BLOCK_CALL_FUNC, data2: VALUE) -> VALUE;
+
+    /// Wraps a data pointer in a new Ruby object.
+    ///
+    /// This is the equivalent of `TypedData_Wrap_Struct`.
+    ///
+    /// * `class` - the class of the new object, or `0` for an object hidden
+    /// from Ruby code
+    /// * `datap` - the data to wrap; ownership passes to the object, which
+    /// releases it with the type's `dfree`
+    /// * `data_type` - an [`rb_data_type_t`], usually a `static`
+    /// * Returns a new [`T_DATA`] object
+    ///
+    /// # Safety
+    ///
+    /// * `data_type` must outlive the object (i.e. be `'static`)
+    /// * `datap` must be valid for the type's callbacks
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-C+struct+to+Ruby+object)
+    ///
+    //+ c-func: gc.c `VALUE rb_data_typed_object_wrap(VALUE klass, void *datap, const rb_data_type_t *)`
+    pub fn rb_data_typed_object_wrap(class: VALUE, datap: *mut c_void, data_type: *const rb_data_type_t) -> VALUE;
+
+    /// Allocates zeroed memory and wraps it in a new Ruby object.
+    ///
+    /// This is the equivalent of `TypedData_Make_Struct`.
+    ///
+    /// * `class` - the class of the new object
+    /// * `size` - the number of bytes to allocate
+    /// * `data_type` - an [`rb_data_type_t`], usually a `static`
+    /// * Returns a new [`T_DATA`] object, whose data can be accessed with
+    /// [`RTYPEDDATA_DATA`]
+    ///
+    /// # Safety
+    ///
+    /// * `data_type` must outlive the object (i.e. be `'static`)
+    /// * The memory is allocated with Ruby's allocator, so `dfree` must release
+    /// it with `ruby_xfree`, not Rust's allocator
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`NoMemoryError`](rb_eNoMemError)
+    ///     * if the memory cannot be allocated
+    ///
+    //+ c-func: gc.c `VALUE rb_data_typed_object_zalloc(VALUE klass, size_t size, const rb_data_type_t *type)`
+    pub fn rb_data_typed_object_zalloc(class: VALUE, size: size_t, data_type: *const rb_data_type_t) -> VALUE;
+
+    /// Returns the data pointer wrapped by a typed data object, after checking
+    /// that it is of the given type (or a type inheriting from it through
+    /// `parent`).
+    ///
+    /// This is the equivalent of `TypedData_Get_Struct`.
+    ///
+    /// * `obj` - any Ruby object
+    /// * `data_type` - the expected [`rb_data_type_t`]
+    /// * Returns the data pointer
+    ///
+    /// # Safety
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`TypeError`](rb_eTypeError)
+    ///     * if `obj` is not a typed data object of `data_type`
+    ///
+    //+ c-func: error.c `void *rb_check_typeddata(VALUE, const rb_data_type_t *)`
+    pub fn rb_check_typeddata(obj: VALUE, data_type: *const rb_data_type_t) -> *mut c_void;
+
+    /// Tells the GC to treat the `VALUE` stored at `addr` as a root, keeping
+    /// whatever object it holds alive, until [`rb_gc_unregister_address`].
+    ///
+    /// This is usually done for `VALUE`s stored in global variables.
+    /// [`Rooted`](struct.Rooted.html) does this for a heap-allocated `VALUE`.
+    ///
+    /// * `addr` - the address of a `VALUE`, which may be changed later
+    ///
+    /// # Safety
+    ///
+    /// * `addr` must stay valid until it is unregistered (or forever)
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Ruby+Global+Variables)
+    ///
+    //+ c-func: gc.c `void rb_gc_register_address(VALUE*)`
+    pub fn rb_gc_register_address(addr: *mut VALUE);
+
+    /// Stops treating the `VALUE` stored at `addr` as a root.
+    ///
+    /// * `addr` - an address passed to [`rb_gc_register_address`]
+    ///
+    /// # Safety
+    ///
+    /// No known issues.
+    ///
+    //+ c-func: gc.c `void rb_gc_unregister_address(VALUE*)`
+    pub fn rb_gc_unregister_address(addr: *mut VALUE);
+
+    /// Keeps an object alive (and in place) for the rest of the process.
+    ///
+    /// Unlike [`rb_gc_register_address`], this roots the object itself rather
+    /// than a location, so there's no way to undo it.
+    ///
+    /// * `obj` - any Ruby object
+    ///
+    /// # Safety
+    ///
+    /// No known issues.
+    ///
+    //+ c-func: gc.c `void rb_gc_register_mark_object(VALUE)`
+    pub fn rb_gc_register_mark_object(obj: VALUE);
+
+    /// Allocates memory with Ruby's allocator, counting it towards the
+    /// malloc-triggered garbage collection.
+    ///
+    /// * `size` - the number of bytes to allocate
+    /// * Returns a pointer to the allocated memory, to be released with
+    /// [`ruby_xfree`]
+    ///
+    /// # Safety
+    ///
+    /// * Must be called on a Ruby thread, as it may start a garbage collection
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`NoMemoryError`](rb_eNoMemError)
+    ///     * if the memory cannot be allocated, even after a garbage collection
+    ///
+    /// # Ruby Documentation
+    ///
+    /// * [2.5](https://ruby-doc.org/core-2.5.1/doc/extension_rdoc.html#label-Allocate+memory)
+    ///
+    /// # Miscellaneous
+    ///
+    /// * Defined in `gc.c` and declared in `ruby/defines.h`, also known as `ALLOC`.
+    pub fn ruby_xmalloc(size: size_t) -> *mut c_void;
+
+    /// Like [`ruby_xmalloc`], for `n` elements of `size` bytes each.
+    ///
+    /// * `n` - the number of elements
+    /// * `size` - the size of each element
+    /// * Returns a pointer to the allocated memory, to be released with
+    /// [`ruby_xfree`]
+    ///
+    /// # Safety
+    ///
+    /// * See [`ruby_xmalloc`](fn.ruby_xmalloc.html#safety)
+    ///
+    /// ## Exceptions
+    ///
+    /// * [`ArgumentError`](rb_eArgError)
+    ///     * if `n * size` overflows
+    /// * See also [`ruby_xmalloc`](fn.ruby_xmalloc.html#exceptions)
+    ///
+    /// # Miscellaneous
+    ///
+    /// * Defined in `gc.c` and declared in `ruby/defines.h`, also known as `ALLOC_N`.
+    pub fn ruby_xmalloc2(n: size_t, size: size_t) -> *mut c_void;
+
+    /// Like [`ruby_xmalloc2`], but the memory is zeroed.
+    ///
+    /// # Safety
+    ///
+    /// * See [`ruby_xmalloc2`](fn.ruby_xmalloc2.html#safety)
+    ///
+    /// # Miscellaneous
+    ///
+    /// * Defined in `gc.c` and declared in `ruby/defines.h`, also known as `ZALLOC_N`.
+    pub fn ruby_xcalloc(n: size_t, size: size_t) -> *mut c_void;
+
+    /// Resizes memory allocated with Ruby's allocator, preserving its contents.
+    ///
+    /// * `ptr` - memory allocated by [`ruby_xmalloc`] and friends, or null
+    /// * `size` - the new size in bytes
+    /// * Returns a pointer to the resized memory, which may have moved
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must be null or have been allocated by Ruby's allocator
+    /// * See also [`ruby_xmalloc`](fn.ruby_xmalloc.html#safety)
+    ///
+    /// ## Exceptions
+    ///
+    /// * See [`ruby_xmalloc`](fn.ruby_xmalloc.html#exceptions)
+    ///
+    /// # Miscellaneous
+    ///
+    /// * Defined in `gc.c` and declared in `ruby/defines.h`. `REALLOC_N` uses
+    /// `ruby_xrealloc2` instead, which takes an element count like
+    /// [`ruby_xmalloc2`].
+    pub fn ruby_xrealloc(ptr: *mut c_void, size: size_t) -> *mut c_void;
+
+    /// Releases memory allocated with Ruby's allocator.
+    ///
+    /// * `ptr` - memory allocated by [`ruby_xmalloc`] and friends, or null
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must be null or have been allocated by Ruby's allocator, and not
+    /// already released
+    ///
+    /// # Miscellaneous
+    ///
+    /// * Defined in `gc.c` and declared in `ruby/defines.h`.
+    pub fn ruby_xfree(ptr: *mut c_void);
+}
+
+tests! {
+    use super::*;
+    use super::super::intern;
+    use super::super::testing::{Assertions, ToRuby, lazy_eval, c_bool};
+    use std::ptr::{null, null_mut};
+
+    use std::cell::Cell;
+    use std::ffi::{CStr, CString};
+    use std::slice;
+
+    extern "C" fn __test_data_type_free__(data: *mut c_void) {
+        unsafe { drop(Box::from_raw(data as *mut u32)) }
+    }
+
+    static TEST_DATA_TYPE: rb_data_type_t = rb_data_type_t {
+        wrap_struct_name: cstr!("TestDataType"),
+        function: rb_data_type_function_t {
+            dmark: None,
+            dfree: Some(__test_data_type_free__),
+            dsize: None,
+            dcompact: None,
+            reserved: [null_mut()],
+        },
+        parent: null(),
+        data: null_mut(),
+        flags: RUBY_TYPED_FREE_IMMEDIATELY,
+    };
+
+    static TEST_DATA_CHILD_TYPE: rb_data_type_t = rb_data_type_t {
+        wrap_struct_name: cstr!("TestDataChildType"),
+        parent: &TEST_DATA_TYPE,
+        ..TEST_DATA_TYPE
+    };
+
+    extern "C" fn __test_data_zalloc_free__(data: *mut c_void) {
+        unsafe { ruby_xfree(data) }
+    }
+
+    static TEST_DATA_ZALLOC_TYPE: rb_data_type_t = rb_data_type_t {
+        wrap_struct_name: cstr!("TestDataZallocType"),
+        function: rb_data_type_function_t {
+            dmark: None,
+            dfree: Some(__test_data_zalloc_free__),
+            dsize: None,
+            dcompact: None,
+            reserved: [null_mut()],
+        },
+        parent: null(),
+        data: null_mut(),
+        flags: RUBY_TYPED_FREE_IMMEDIATELY,
+    };
+
+    #[test]
+    fn test_false(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("false"), unsafe { Qfalse });
+    }
+
+    #[test]
+    fn test_true(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("true"), unsafe{ Qtrue });
+    }
+
+    #[test]
+    fn test_nil(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("nil"), unsafe { Qnil });
+    }
+
+    #[test]
+    fn test_m_kernel(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Kernel"), unsafe { rb_mKernel });
+    }
+
+    #[test]
+    fn test_m_comparable(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Comparable"), unsafe { rb_mComparable });
+    }
+
+    #[test]
+    fn test_m_enumerable(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Enumerable"), unsafe { rb_mEnumerable });
+    }
+
+    #[test]
+    fn test_m_errno(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Errno"), unsafe { rb_mErrno });
+    }
+
+    #[test]
+    fn test_m_file_test(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::FileTest"), unsafe { rb_mFileTest });
+    }
+
+    #[test]
+    fn test_m_gc(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::GC"), unsafe { rb_mGC });
+    }
+
+    #[test]
+    fn test_m_math(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Math"), unsafe { rb_mMath });
+    }
+
+    #[test]
+    fn test_m_process(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Process"), unsafe { rb_mProcess });
+    }
+
+    #[test]
+    fn test_m_wait_readable(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::IO::WaitReadable"), unsafe { rb_mWaitReadable });
+    }
+
+    #[test]
+    fn test_m_wait_writable(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::IO::WaitWritable"), unsafe { rb_mWaitWritable });
+    }
+
+    #[test]
+    fn test_c_basic_object(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::BasicObject"), unsafe { rb_cBasicObject });
+    }
+
+    #[test]
+    fn test_c_object(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Object"), unsafe { rb_cObject });
+    }
+
+    #[test]
+    fn test_c_array(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Array"), unsafe { rb_cArray });
+    }
+
+    #[test]
+    fn test_c_binding(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Binding"), unsafe { rb_cBinding });
+    }
+
+    #[test]
+    fn test_c_class(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Class"), unsafe { rb_cClass });
     }
 
     #[test]
-    fn test_true(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("true"), unsafe{ Qtrue });
+    fn test_c_dir(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Dir"), unsafe { rb_cDir });
     }
 
     #[test]
-    fn test_nil(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("nil"), unsafe { Qnil });
+    fn test_c_encoding(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Encoding"), unsafe { rb_cEncoding });
     }
 
     #[test]
-    fn test_m_kernel(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Kernel"), unsafe { rb_mKernel });
+    fn test_c_enumerator(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Enumerator"), unsafe { rb_cEnumerator });
     }
 
     #[test]
-    fn test_m_comparable(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Comparable"), unsafe { rb_mComparable });
+    fn test_c_false_class(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::FalseClass"), unsafe { rb_cFalseClass });
     }
 
     #[test]
-    fn test_m_enumerable(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Enumerable"), unsafe { rb_mEnumerable });
+    fn test_c_file(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::File"), unsafe { rb_cFile });
     }
 
     #[test]
-    fn test_m_errno(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Errno"), unsafe { rb_mErrno });
+    fn test_c_complex(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Complex"), unsafe { rb_cComplex });
     }
 
     #[test]
-    fn test_m_file_test(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::FileTest"), unsafe { rb_mFileTest });
+    fn test_c_float(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Float"), unsafe { rb_cFloat });
     }
 
     #[test]
-    fn test_m_gc(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::GC"), unsafe { rb_mGC });
+    fn test_c_hash(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Hash"), unsafe { rb_cHash });
     }
 
     #[test]
-    fn test_m_math(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Math"), unsafe { rb_mMath });
+    fn test_c_io(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::IO"), unsafe { rb_cIO });
+    }
+
+    #[test]
+    fn test_c_integer(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Integer"), unsafe { rb_cInteger });
+    }
+
+    #[test]
+    fn test_c_match(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::MatchData"), unsafe { rb_cMatch });
+    }
+
+    #[test]
+    fn test_c_method(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Method"), unsafe { rb_cMethod });
+    }
+
+    #[test]
+    fn test_c_module(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Module"), unsafe { rb_cModule });
+    }
+
+    #[test]
+    fn test_c_nil_class(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::NilClass"), unsafe { rb_cNilClass });
+    }
+
+    #[test]
+    fn test_c_numeric(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Numeric"), unsafe { rb_cNumeric });
+    }
+
+    #[test]
+    fn test_c_proc(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Proc"), unsafe { rb_cProc });
+    }
+
+    #[test]
+    fn test_c_random(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Random"), unsafe { rb_cRandom });
+    }
+
+    #[test]
+    fn test_c_range(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Range"), unsafe { rb_cRange });
+    }
+
+    #[test]
+    fn test_c_rational(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Rational"), unsafe { rb_cRational });
+    }
+
+    #[test]
+    fn test_c_regexp(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Regexp"), unsafe { rb_cRegexp });
+    }
+
+    #[test]
+    fn test_c_stat(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::File::Stat"), unsafe { rb_cStat });
+    }
+
+    #[test]
+    fn test_c_string(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::String"), unsafe { rb_cString });
+    }
+
+    #[test]
+    fn test_c_struct(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Struct"), unsafe { rb_cStruct });
+    }
+
+    #[test]
+    fn test_c_symbol(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Symbol"), unsafe { rb_cSymbol });
+    }
+
+    #[test]
+    fn test_c_thread(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Thread"), unsafe { rb_cThread });
+    }
+
+    #[test]
+    fn test_c_time(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Time"), unsafe { rb_cTime });
+    }
+
+    #[test]
+    fn test_c_true_class(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::TrueClass"), unsafe { rb_cTrueClass });
+    }
+
+    #[test]
+    fn test_c_unbound_method(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::UnboundMethod"), unsafe { rb_cUnboundMethod });
+    }
+
+    #[test]
+    fn test_e_exception(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Exception"), unsafe { rb_eException });
+    }
+
+    #[test]
+    fn test_e_standard_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::StandardError"), unsafe { rb_eStandardError });
+    }
+
+    #[test]
+    fn test_e_system_exit(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::SystemExit"), unsafe { rb_eSystemExit });
+    }
+
+    #[test]
+    fn test_e_interrupt(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Interrupt"), unsafe { rb_eInterrupt });
+    }
+
+    #[test]
+    fn test_e_signal(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::SignalException"), unsafe { rb_eSignal });
+    }
+
+    #[test]
+    fn test_e_arg_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::ArgumentError"), unsafe { rb_eArgError });
+    }
+
+    #[test]
+    fn test_e_eof_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::EOFError"), unsafe { rb_eEOFError });
+    }
+
+    #[test]
+    fn test_e_index_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::IndexError"), unsafe { rb_eIndexError });
+    }
+
+    #[test]
+    fn test_e_stop_iteration(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::StopIteration"), unsafe { rb_eStopIteration });
+    }
+
+    #[test]
+    fn test_e_key_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::KeyError"), unsafe { rb_eKeyError });
+    }
+
+    #[test]
+    fn test_e_range_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::RangeError"), unsafe { rb_eRangeError });
+    }
+
+    #[test]
+    fn test_e_io_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::IOError"), unsafe { rb_eIOError });
+    }
+
+    #[test]
+    fn test_e_runtime_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::RuntimeError"), unsafe { rb_eRuntimeError });
+    }
+
+    #[test]
+    fn test_e_security_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::SecurityError"), unsafe { rb_eSecurityError });
+    }
+
+    #[test]
+    fn test_e_system_call_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::SystemCallError"), unsafe { rb_eSystemCallError });
+    }
+
+    #[test]
+    fn test_e_thread_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::ThreadError"), unsafe { rb_eThreadError });
+    }
+
+    #[test]
+    fn test_e_type_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::TypeError"), unsafe { rb_eTypeError });
+    }
+
+    #[test]
+    fn test_e_zero_div_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::ZeroDivisionError"), unsafe { rb_eZeroDivError });
+    }
+
+    #[test]
+    fn test_e_not_imp_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::NotImplementedError"), unsafe { rb_eNotImpError });
+    }
+
+    #[test]
+    fn test_e_no_mem_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::NoMemoryError"), unsafe { rb_eNoMemError });
+    }
+
+    #[test]
+    fn test_e_no_method_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::NoMethodError"), unsafe { rb_eNoMethodError });
+    }
+
+    #[test]
+    fn test_e_float_domain_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::FloatDomainError"), unsafe { rb_eFloatDomainError });
+    }
+
+    #[test]
+    fn test_e_local_jump_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::LocalJumpError"), unsafe { rb_eLocalJumpError });
+    }
+
+    #[test]
+    fn test_e_system_stack_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::SystemStackError"), unsafe { rb_eSysStackError });
+    }
+
+    #[test]
+    fn test_e_regexp_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::RegexpError"), unsafe { rb_eRegexpError });
     }
 
     #[test]
-    fn test_m_process(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Process"), unsafe { rb_mProcess });
+    fn test_e_encoding_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::EncodingError"), unsafe { rb_eEncodingError });
     }
 
     #[test]
-    fn test_m_wait_readable(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::IO::WaitReadable"), unsafe { rb_mWaitReadable });
+    fn test_e_enc_compat_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Encoding::CompatibilityError"), unsafe { rb_eEncCompatError });
     }
 
     #[test]
-    fn test_m_wait_writable(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::IO::WaitWritable"), unsafe { rb_mWaitWritable });
+    fn test_e_script_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::ScriptError"), unsafe { rb_eScriptError });
     }
 
     #[test]
-    fn test_c_basic_object(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::BasicObject"), unsafe { rb_cBasicObject });
+    fn test_e_name_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::NameError"), unsafe { rb_eNameError });
     }
 
     #[test]
-    fn test_c_object(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Object"), unsafe { rb_cObject });
+    fn test_e_syntax_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::SyntaxError"), unsafe { rb_eSyntaxError });
     }
 
     #[test]
-    fn test_c_array(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Array"), unsafe { rb_cArray });
+    fn test_e_load_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::LoadError"), unsafe { rb_eLoadError });
     }
 
     #[test]
-    fn test_c_binding(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Binding"), unsafe { rb_cBinding });
+    fn test_e_math_domain_error(assert: &mut Assertions) {
+        assert.rb_eq(lazy_eval("::Math::DomainError"), unsafe { rb_eMathDomainError });
     }
 
     #[test]
-    fn test_c_class(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Class"), unsafe { rb_cClass });
+    fn test_types(assert: &mut Assertions) {
+        // FIXME: Test more types
+        let objects = unsafe {
+            vec![
+                ("T_NONE",     T_NONE,     None), // Not sure we can test this
+                ("T_OBJECT",   T_OBJECT,   Some(intern::rb_class_new_instance(0, null(), rb_cObject))),
+                ("T_CLASS",    T_CLASS,    Some(rb_cObject)),
+                ("T_MODULE",   T_MODULE,   Some(rb_mKernel)),
+                ("T_FLOAT",    T_FLOAT,    Some(rb_funcall!(INT2NUM(1), rb_intern(cstr!("to_f"))))),
+                ("T_STRING",   T_STRING,   Some("foo".to_ruby())),
+                ("T_REGEXP",   T_REGEXP,   Some(intern::rb_class_new_instance(1, &"[a-z]".to_ruby(), rb_cRegexp))),
+                ("T_ARRAY",    T_ARRAY,    Some(intern::rb_ary_new())),
+                ("T_HASH",     T_HASH,     Some(intern::rb_hash_new())),
+                ("T_STRUCT",   T_STRUCT,   None),
+                ("T_BIGNUM",   T_BIGNUM,   Some(rb_funcall!(INT2NUM(2), rb_intern(cstr!("**")), INT2NUM(100)))),
+                ("T_FILE",     T_FILE,     None), // Need to get a file descriptor for this
+                ("T_DATA",     T_DATA,     Some(rb_data_typed_object_wrap(rb_cObject, null_mut(), &TEST_DATA_TYPE))),
+                ("T_MATCH",    T_MATCH,    None), // Can do this with funcall
+                ("T_COMPLEX",  T_COMPLEX,  Some(rb_funcall!(INT2NUM(1), rb_intern(cstr!("to_c"))))),
+                ("T_RATIONAL", T_RATIONAL, Some(rb_funcall!(INT2NUM(1), rb_intern(cstr!("to_r"))))),
+                ("T_NIL",      T_NIL,      Some(Qnil)),
+                ("T_TRUE",     T_TRUE,     Some(Qtrue)),
+                ("T_FALSE",    T_FALSE,    Some(Qfalse)),
+                ("T_SYMBOL",   T_SYMBOL,   Some(rb_id2sym(rb_intern(cstr!("foo"))))),
+                ("T_FIXNUM",   T_FIXNUM,   Some(INT2NUM(1))),
+                ("T_UNDEF",    T_UNDEF,    None), // Not sure we can test this
+                ("T_IMEMO",    T_IMEMO,    None), // Not sure we can test this
+                ("T_NODE",     T_NODE,     None), // Not sure we can test this
+                ("T_ICLASS",   T_ICLASS,   None), // Not sure we can test this
+                ("T_ZOMBIE",   T_ZOMBIE,   None) // Not sure we can test this
+            ]
+        };
+
+        for (name, rtype, value) in objects.into_iter() {
+            if let Some(v) = value {
+                assert.rs_eq_msg(c_bool(unsafe { RB_TYPE_P(v, rtype) }), true, Some(format!("type of VALUE is {}", name)));
+            } else {
+                // Sanity check
+                assert.rs_eq_msg(rtype.0 >= 0x00, true, Some(format!("{} >= 0x00", name)));
+                assert.rs_eq_msg(rtype.0 < 0x1f, true, Some(format!("{} < 0x1f", name)));
+            }
+        }
+
+        // Ensure that it doesn't only return true
+        assert.rs_eq(c_bool(unsafe { RB_TYPE_P("foo".to_ruby(), T_HASH) }), false);
     }
 
     #[test]
-    fn test_c_dir(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Dir"), unsafe { rb_cDir });
+    fn test_class_of(assert: &mut Assertions) {
+        assert.rb_eq(unsafe { rb_cString }, unsafe { CLASS_OF("foo".to_ruby()) });
+
+        // `rb_obj_class` on a class returns the class itself, `CLASS_OF` returns the singleton class
+        assert.rb_ne(unsafe { intern::rb_obj_class(rb_cString) }, unsafe { CLASS_OF(rb_cString) });
+        assert.rb_eq(lazy_eval("String.singleton_class"), unsafe { CLASS_OF(rb_cString) });
     }
 
     #[test]
-    fn test_c_encoding(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Encoding"), unsafe { rb_cEncoding });
+    fn test_numeric_conversions(assert: &mut Assertions) {
+        let int_max = c_int::MAX;
+        let long_max = c_long::MAX;
+        let long_long_max = c_longlong::MAX;
+
+        let val = -int_max + 1;
+        assert.rb_eq(lazy_eval(&format!("{}", val)), unsafe { INT2NUM(val) });
+        assert.rs_eq(val, unsafe { NUM2INT(INT2NUM(val)) });
+
+        let val = (int_max as c_uint) + 1;
+        assert.rb_eq(lazy_eval(&format!("{}", val)), unsafe { UINT2NUM(val) });
+        assert.rs_eq(val, unsafe { NUM2UINT(UINT2NUM(val)) });
+
+        let val = -long_max + 1;
+        assert.rb_eq(lazy_eval(&format!("{}", val)), unsafe { LONG2NUM(val) });
+        assert.rs_eq(val, unsafe { NUM2LONG(LONG2NUM(val)) });
+
+        let val = (long_max as c_ulong) + 1;
+        assert.rb_eq(lazy_eval(&format!("{}", val)), unsafe { ULONG2NUM(val) });
+        assert.rs_eq(val, unsafe { NUM2ULONG(ULONG2NUM(val)) });
+
+        let val = -long_long_max + 1;
+        assert.rb_eq(lazy_eval(&format!("{}", val)), unsafe { LL2NUM(val) });
+        assert.rs_eq(val, unsafe { NUM2LL(LL2NUM(val)) });
+
+        let val = (long_long_max as c_ulonglong) + 1;
+        assert.rb_eq(lazy_eval(&format!("{}", val)), unsafe { ULL2NUM(val) });
+        assert.rs_eq(val, unsafe { NUM2ULL(ULL2NUM(val)) });
     }
 
     #[test]
-    fn test_c_enumerator(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Enumerator"), unsafe { rb_cEnumerator });
+    fn test_rstring_len(assert: &mut Assertions) {
+        assert.rs_eq(unsafe { RSTRING_LEN("".to_ruby()) }, 0);
+        assert.rs_eq(unsafe { RSTRING_LEN("foo".to_ruby()) }, 3);
+        assert.rs_eq(unsafe { RSTRING_LEN("this is longer".to_ruby()) }, 14);
+        assert.rs_eq(unsafe { RSTRING_LEN("☠️".to_ruby()) }, 6);
     }
 
     #[test]
-    fn test_c_false_class(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::FalseClass"), unsafe { rb_cFalseClass });
+    fn test_rstring_ptr(assert: &mut Assertions) {
+        let cstr = unsafe { CStr::from_ptr(RSTRING_PTR("foobar".to_ruby()) as *const c_char) };
+        let unicode = unsafe { CStr::from_ptr(RSTRING_PTR("☠️".to_ruby()) as *const c_char) };
+        assert.rs_eq(cstr.to_string_lossy(), "foobar");
+        assert.rs_eq(unicode.to_string_lossy(), "☠️");
     }
 
     #[test]
-    fn test_c_file(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::File"), unsafe { rb_cFile });
+    fn test_rarray_len(assert: &mut Assertions) {
+        let array = unsafe { intern::rb_ary_new() };
+
+        assert.rs_eq(unsafe { RARRAY_LEN(array) }, 0);
+
+        unsafe { intern::rb_ary_push(array, "foo".to_ruby()); }
+        assert.rs_eq(unsafe { RARRAY_LEN(array) }, 1);
+
+        unsafe { intern::rb_ary_push(array, "bar".to_ruby()); }
+        assert.rs_eq(unsafe { RARRAY_LEN(array) }, 2);
     }
 
     #[test]
-    fn test_c_complex(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Complex"), unsafe { rb_cComplex });
+    fn test_rhash_size(assert: &mut Assertions) {
+        let hash = unsafe { intern::rb_hash_new() };
+
+        assert.rs_eq(unsafe { RHASH_SIZE(hash) }, 0);
+
+        unsafe { intern::rb_hash_aset(hash, "foo".to_ruby(), "bar".to_ruby()); }
+        assert.rs_eq(unsafe { RHASH_SIZE(hash) }, 1);
+
+        unsafe { intern::rb_hash_aset(hash, "baz".to_ruby(), "qux".to_ruby()); }
+        assert.rs_eq(unsafe { RHASH_SIZE(hash) }, 2);
     }
 
     #[test]
-    fn test_c_float(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Float"), unsafe { rb_cFloat });
+    fn test_id(assert: &mut Assertions) {
+        let foo1 = unsafe { rb_intern(cstr!("foo")) };
+        let foo2 = unsafe { rb_intern(CString::new("foo").unwrap().into_raw()) };
+        let foo3 = unsafe { rb_intern2("foobar".as_ptr() as *const c_char, 3 as c_long) };
+        let foo4 = unsafe { rb_intern_str("foo".to_ruby()) };
+
+        let bar = unsafe { rb_intern(cstr!("bar")) };
+
+        assert.rs_eq(foo1, foo1);
+        assert.rs_eq(foo1, foo2);
+        assert.rs_eq(foo1, foo3);
+        assert.rs_eq(foo1, foo4);
+
+        assert.rs_ne(foo1, bar);
+        assert.rs_ne(foo2, bar);
+        assert.rs_ne(foo3, bar);
+        assert.rs_ne(foo4, bar);
+
+        assert.rs_eq(foo1, unsafe { rb_sym2id(rb_id2sym(foo1)) });
+        assert.rs_eq(foo2, unsafe { rb_sym2id(rb_id2sym(foo2)) });
+        assert.rs_eq(foo3, unsafe { rb_sym2id(rb_id2sym(foo3)) });
+        assert.rs_eq(foo4, unsafe { rb_sym2id(rb_id2sym(foo4)) });
+
+        assert.rs_ne(foo1, unsafe { rb_sym2id(rb_id2sym(bar)) });
+        assert.rs_ne(foo2, unsafe { rb_sym2id(rb_id2sym(bar)) });
+        assert.rs_ne(foo3, unsafe { rb_sym2id(rb_id2sym(bar)) });
+        assert.rs_ne(foo4, unsafe { rb_sym2id(rb_id2sym(bar)) });
+
+        assert.rb_eq(lazy_eval(":foo"), unsafe { rb_id2sym(foo1) });
+        assert.rb_eq(lazy_eval(":foo"), unsafe { rb_id2sym(foo2) });
+        assert.rb_eq(lazy_eval(":foo"), unsafe { rb_id2sym(foo3) });
+        assert.rb_eq(lazy_eval(":foo"), unsafe { rb_id2sym(foo4) });
+        assert.rb_eq(lazy_eval(":bar"), unsafe { rb_id2sym(bar) });
+
+        assert.rb_eq(lazy_eval("'foo'"), unsafe { rb_id2str(foo1) });
+        assert.rb_eq(lazy_eval("'foo'"), unsafe { rb_id2str(foo2) });
+        assert.rb_eq(lazy_eval("'foo'"), unsafe { rb_id2str(foo3) });
+        assert.rb_eq(lazy_eval("'foo'"), unsafe { rb_id2str(foo4) });
+        assert.rb_eq(lazy_eval("'bar'"), unsafe { rb_id2str(bar) });
     }
 
     #[test]
-    fn test_c_hash(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Hash"), unsafe { rb_cHash });
+    fn test_define_class(assert: &mut Assertions) {
+        let foo = unsafe { rb_define_class(cstr!("TestDefineClass__Foo"), rb_cObject) };
+        let bar = unsafe { rb_define_class(cstr!("TestDefineClass__Bar"), foo) };
+
+        assert.rb_eq(lazy_eval("::TestDefineClass__Foo"), foo);
+        assert.rb_eq(lazy_eval("::TestDefineClass__Bar"), bar);
+
+        assert.rb_eq(lazy_eval("::TestDefineClass__Foo.class"), unsafe { rb_cClass });
+        assert.rb_eq(lazy_eval("::TestDefineClass__Bar.class"), unsafe { rb_cClass });
+
+        assert.rb_eq(lazy_eval("::TestDefineClass__Foo.superclass"), unsafe { rb_cObject });
+        assert.rb_eq(lazy_eval("::TestDefineClass__Bar.superclass"), foo);
     }
 
     #[test]
-    fn test_c_io(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::IO"), unsafe { rb_cIO });
+    fn test_define_module(assert: &mut Assertions) {
+        let foo = unsafe { rb_define_module(cstr!("TestDefineModule__Foo")) };
+        let bar = unsafe { rb_define_module_under(foo, cstr!("Bar")) };
+        let baz = unsafe { rb_define_class_under(bar, cstr!("Baz"), rb_cObject) };
+        let qux = unsafe { rb_define_class_under(bar, cstr!("Qux"), baz) };
+
+        assert.rb_eq(lazy_eval("::TestDefineModule__Foo"), foo);
+        assert.rb_eq(lazy_eval("::TestDefineModule__Foo::Bar"), bar);
+        assert.rb_eq(lazy_eval("::TestDefineModule__Foo::Bar::Baz"), baz);
+        assert.rb_eq(lazy_eval("::TestDefineModule__Foo::Bar::Qux"), qux);
+
+        assert.rb_eq(lazy_eval("::TestDefineModule__Foo.class"), unsafe { rb_cModule });
+        assert.rb_eq(lazy_eval("::TestDefineModule__Foo::Bar.class"), unsafe { rb_cModule });
+        assert.rb_eq(lazy_eval("::TestDefineModule__Foo::Bar::Baz.class"), unsafe { rb_cClass });
+
+        assert.rb_eq(lazy_eval("::TestDefineModule__Foo::Bar.name"), "TestDefineModule__Foo::Bar".to_ruby());
+        assert.rb_eq(lazy_eval("::TestDefineModule__Foo::Bar::Qux.superclass"), baz);
+
+        assert.rb_eq(foo, unsafe { rb_define_module(cstr!("TestDefineModule__Foo")) });
+        assert.rb_eq(baz, unsafe { rb_define_class_under(bar, cstr!("Baz"), rb_cObject) });
     }
 
     #[test]
-    fn test_c_integer(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Integer"), unsafe { rb_cInteger });
+    fn test_define_method(assert: &mut Assertions) {
+        extern "C" fn __test_define_method_arity_0__(_self: VALUE) -> VALUE {
+            "__test_define_method_arity_0__ works!".to_ruby()
+        }
+
+        unsafe {
+            rb_define_method(
+                rb_cObject,
+                cstr!("__test_define_method_arity_0__"),
+                ANYARGS::from_arity_1(__test_define_method_arity_0__),
+                0
+            );
+        }
+
+        assert.rb_eq(
+            lazy_eval("::Object.new.__test_define_method_arity_0__"),
+            "__test_define_method_arity_0__ works!".to_ruby()
+        );
+
+        extern "C" fn __test_define_method_arity_3__(_self: VALUE, foo_sym: VALUE, bar_sym: VALUE, baz_sym: VALUE) -> VALUE {
+            if unsafe { rb_sym2id(foo_sym) != rb_intern(cstr!("foo")) } {
+                "__test_define_method_arity_3__ failed (expected :foo for first argument)".to_ruby()
+            } else if unsafe { rb_sym2id(bar_sym) != rb_intern(cstr!("bar")) } {
+                "__test_define_method_arity_3__ failed (expected :bar for second argument)".to_ruby()
+            } else if unsafe { rb_sym2id(baz_sym) != rb_intern(cstr!("baz")) } {
+                "__test_define_method_arity_3__ failed (expected :baz for third argument)".to_ruby()
+            } else {
+                "__test_define_method_arity_3__ works!".to_ruby()
+            }
+        }
+
+        unsafe {
+            rb_define_method(
+                rb_cObject,
+                cstr!("__test_define_method_arity_3__"),
+                ANYARGS::from_arity_4(__test_define_method_arity_3__),
+                3
+            );
+        }
+
+        assert.rb_eq(
+            lazy_eval("::Object.new.__test_define_method_arity_3__(:foo, :bar, :baz)"),
+            "__test_define_method_arity_3__ works!".to_ruby()
+        );
     }
 
-    #[test]
-    fn test_c_match(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::MatchData"), unsafe { rb_cMatch });
-    }
+    #[test]
+    fn test_define_module_function(assert: &mut Assertions) {
+        extern "C" fn __test_define_module_function_arity_0__(_self: VALUE) -> VALUE {
+            "__test_define_module_function_arity_0__ works!".to_ruby()
+        }
+
+        unsafe {
+            rb_define_module_function(
+                rb_mKernel,
+                cstr!("__test_define_module_function_arity_0__"),
+                ANYARGS::from_arity_1(__test_define_module_function_arity_0__),
+                0
+            );
+        }
+
+        assert.rb_eq(
+            lazy_eval("::Kernel.__test_define_module_function_arity_0__"),
+            "__test_define_module_function_arity_0__ works!".to_ruby()
+        );
 
-    #[test]
-    fn test_c_method(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Method"), unsafe { rb_cMethod });
-    }
+        assert.rb_eq(
+            lazy_eval("__test_define_module_function_arity_0__"),
+            "__test_define_module_function_arity_0__ works!".to_ruby()
+        );
 
-    #[test]
-    fn test_c_module(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Module"), unsafe { rb_cModule });
-    }
+        extern "C" fn __test_define_module_function_arity_3__(_self: VALUE, foo_sym: VALUE, bar_sym: VALUE, baz_sym: VALUE) -> VALUE {
+            if unsafe { rb_sym2id(foo_sym) != rb_intern(cstr!("foo")) } {
+                "__test_define_module_function_arity_3__ failed (expected :foo for first argument)".to_ruby()
+            } else if unsafe { rb_sym2id(bar_sym) != rb_intern(cstr!("bar")) } {
+                "__test_define_module_function_arity_3__ failed (expected :bar for second argument)".to_ruby()
+            } else if unsafe { rb_sym2id(baz_sym) != rb_intern(cstr!("baz")) } {
+                "__test_define_module_function_arity_3__ failed (expected :baz for third argument)".to_ruby()
+            } else {
+                "__test_define_module_function_arity_3__ works!".to_ruby()
+            }
+        }
 
-    #[test]
-    fn test_c_nil_class(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::NilClass"), unsafe { rb_cNilClass });
-    }
+        unsafe {
+            rb_define_method(
+                rb_mKernel,
+                cstr!("__test_define_module_function_arity_3__"),
+                ANYARGS::from_arity_4(__test_define_module_function_arity_3__),
+                3
+            );
+        }
 
-    #[test]
-    fn test_c_numeric(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Numeric"), unsafe { rb_cNumeric });
-    }
+        assert.rb_eq(
+            lazy_eval("::Kernel.__test_define_module_function_arity_3__(:foo, :bar, :baz)"),
+            "__test_define_module_function_arity_3__ works!".to_ruby()
+        );
 
-    #[test]
-    fn test_c_proc(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Proc"), unsafe { rb_cProc });
+        assert.rb_eq(
+            lazy_eval("__test_define_module_function_arity_3__(:foo, :bar, :baz)"),
+            "__test_define_module_function_arity_3__ works!".to_ruby()
+        );
     }
 
     #[test]
-    fn test_c_random(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Random"), unsafe { rb_cRandom });
-    }
+    fn test_undef_method(assert: &mut Assertions) {
+        let class = unsafe {
+            rb_define_class(
+                cstr!("TestUndefClass"),
+                rb_cObject
+            )
+        };
 
-    #[test]
-    fn test_c_range(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Range"), unsafe { rb_cRange });
-    }
+        unsafe { rb_undef_method(class, cstr!("to_s")) };
 
-    #[test]
-    fn test_c_rational(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Rational"), unsafe { rb_cRational });
-    }
+        assert.rb_eq(
+            lazy_eval("::TestUndefClass.new.respond_to?(:to_s)"),
+            unsafe { Qfalse }
+        );
 
-    #[test]
-    fn test_c_regexp(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Regexp"), unsafe { rb_cRegexp });
-    }
+        assert.rb_eq(
+            lazy_eval(r#"
+                exception = begin
+                TestUndefClass.new.to_s
+                rescue => e
+                e
+                end
 
-    #[test]
-    fn test_c_stat(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::File::Stat"), unsafe { rb_cStat });
+                exception.class
+            "#),
+            unsafe { rb_eNoMethodError }
+        );
     }
 
     #[test]
-    fn test_c_string(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::String"), unsafe { rb_cString });
-    }
+    fn test_undef_module_method(assert: &mut Assertions) {
+        extern "C" fn __test_undef_module_method__(_self: VALUE) -> VALUE {
+            "__test_undef_module_method__ works!".to_ruby()
+        }
 
-    #[test]
-    fn test_c_struct(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Struct"), unsafe { rb_cStruct });
-    }
+        unsafe {
+            rb_define_method(
+                rb_mKernel,
+                cstr!("__test_undef_module_method__"),
+                ANYARGS::from_arity_1(__test_undef_module_method__),
+                0
+            );
+        }
 
-    #[test]
-    fn test_c_symbol(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Symbol"), unsafe { rb_cSymbol });
-    }
+        // NOTE: Ideally we would test this just to make sure things are set up correctly,
+        //   but due to the lazy_eval this won't pass.
+        //
+        // assert.rb_eq(
+        //     lazy_eval("::Kernel.__test_undef_module_method__"),
+        //     "__test_undef_module_method__ works!".to_ruby()
+        // );
 
-    #[test]
-    fn test_c_thread(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Thread"), unsafe { rb_cThread });
-    }
+        unsafe {
+            rb_undef_method(
+                rb_mKernel,
+                cstr!("__test_undef_module_method__")
+            );
+        }
 
-    #[test]
-    fn test_c_time(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Time"), unsafe { rb_cTime });
+        assert.rb_eq(
+            lazy_eval("::Kernel.respond_to?(:__test_undef_module_method__)"),
+            unsafe { Qfalse }
+        );
     }
 
     #[test]
-    fn test_c_true_class(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::TrueClass"), unsafe { rb_cTrueClass });
-    }
+    fn test_obj_classname(assert: &mut Assertions) {
+        let class = unsafe { CStr::from_ptr(rb_obj_classname(rb_cObject)) };
+        let module = unsafe { CStr::from_ptr(rb_obj_classname(rb_mKernel)) };
+        let instance = unsafe {
+            let i = intern::rb_class_new_instance(0, null(), rb_cObject);
+            CStr::from_ptr(rb_obj_classname(i))
+        };
 
-    #[test]
-    fn test_c_unbound_method(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::UnboundMethod"), unsafe { rb_cUnboundMethod });
+        assert.rs_eq("Class", class.to_string_lossy());
+        assert.rs_eq("Module", module.to_string_lossy());
+        assert.rs_eq("Object", instance.to_string_lossy());
     }
 
     #[test]
-    fn test_e_exception(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Exception"), unsafe { rb_eException });
-    }
+    fn test_block_given_p(assert: &mut Assertions) {
+        extern "C" fn __test_block_given_p__(_self: VALUE) -> VALUE {
+            if unsafe { rb_block_given_p() != 0 } {
+                unsafe { Qtrue }
+            } else {
+                unsafe { Qfalse }
+            }
+        }
 
-    #[test]
-    fn test_e_standard_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::StandardError"), unsafe { rb_eStandardError });
-    }
+        unsafe {
+            rb_define_method(
+                rb_mKernel,
+                cstr!("__test_block_given_p__"),
+                ANYARGS::from_arity_1(__test_block_given_p__),
+                0
+            );
+        }
 
-    #[test]
-    fn test_e_system_exit(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::SystemExit"), unsafe { rb_eSystemExit });
-    }
+        assert.rb_eq(
+            lazy_eval("__test_block_given_p__"),
+            unsafe { Qfalse }
+        );
 
-    #[test]
-    fn test_e_interrupt(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Interrupt"), unsafe { rb_eInterrupt });
+        assert.rb_eq(
+            lazy_eval("__test_block_given_p__ { }"),
+            unsafe { Qtrue }
+        );
     }
 
     #[test]
-    fn test_e_signal(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::SignalException"), unsafe { rb_eSignal });
-    }
+    fn test_raise(assert: &mut Assertions) {
+        extern "C" fn __test_raise__(_self: VALUE) -> VALUE {
+            unsafe { rb_raise(rb_eArgError, cstr!("%s is %d"), cstr!("the answer"), 42 as c_int) }
+        }
 
-    #[test]
-    fn test_e_arg_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::ArgumentError"), unsafe { rb_eArgError });
-    }
+        unsafe {
+            rb_define_method(
+                rb_mKernel,
+                cstr!("__test_raise__"),
+                ANYARGS::from_arity_1(__test_raise__),
+                0
+            );
+        }
 
-    #[test]
-    fn test_e_eof_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::EOFError"), unsafe { rb_eEOFError });
-    }
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_raise__
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eArgError }
+        );
 
-    #[test]
-    fn test_e_index_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::IndexError"), unsafe { rb_eIndexError });
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_raise__
+                rescue Exception => e
+                e.message
+                end
+            "#),
+            "the answer is 42".to_ruby()
+        );
     }
 
     #[test]
-    fn test_e_stop_iteration(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::StopIteration"), unsafe { rb_eStopIteration });
-    }
+    fn test_protect(assert: &mut Assertions) {
+        extern "C" fn __test_protect_ok__(arg: VALUE) -> VALUE {
+            arg
+        }
 
-    #[test]
-    fn test_e_key_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::KeyError"), unsafe { rb_eKeyError });
-    }
+        extern "C" fn __test_protect_raise__(_arg: VALUE) -> VALUE {
+            unsafe { rb_raise(rb_eTypeError, cstr!("__test_protect_raise__ raised")) }
+        }
 
-    #[test]
-    fn test_e_range_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::RangeError"), unsafe { rb_eRangeError });
-    }
+        extern "C" fn __test_protect_ptr__(count: *mut c_int) -> VALUE {
+            unsafe { *count += 1 };
+            unsafe { Qtrue }
+        }
 
-    #[test]
-    fn test_e_io_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::IOError"), unsafe { rb_eIOError });
-    }
+        let mut state: c_int = 0;
+        let result = unsafe { rb_protect(PROTECT_FUNC::from_value(__test_protect_ok__), "ok".to_ruby(), &mut state) };
 
-    #[test]
-    fn test_e_runtime_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::RuntimeError"), unsafe { rb_eRuntimeError });
-    }
+        assert.rs_eq(state, 0);
+        assert.rb_eq("ok".to_ruby(), result);
+        assert.rb_nil(unsafe { rb_errinfo() });
 
-    #[test]
-    fn test_e_security_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::SecurityError"), unsafe { rb_eSecurityError });
-    }
+        let mut count: c_int = 0;
+        let result = unsafe { rb_protect(PROTECT_FUNC::from_ptr(__test_protect_ptr__), VALUE::from_ptr(&mut count), &mut state) };
 
-    #[test]
-    fn test_e_system_call_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::SystemCallError"), unsafe { rb_eSystemCallError });
-    }
+        assert.rs_eq(state, 0);
+        assert.rs_eq(count, 1);
+        assert.rb_eq(unsafe { Qtrue }, result);
 
-    #[test]
-    fn test_e_thread_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::ThreadError"), unsafe { rb_eThreadError });
-    }
+        let result = unsafe { rb_protect(PROTECT_FUNC::from_value(__test_protect_raise__), Qnil, &mut state) };
+        let errinfo = unsafe { rb_errinfo() };
 
-    #[test]
-    fn test_e_type_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::TypeError"), unsafe { rb_eTypeError });
-    }
+        assert.rs_ne(state, 0);
+        assert.rb_nil(result);
+        assert.rb_eq(unsafe { rb_eTypeError }, unsafe { intern::rb_obj_class(errinfo) });
+        assert.rb_eq(lazy_eval("TypeError.new('__test_protect_raise__ raised').inspect"), unsafe { intern::rb_inspect(errinfo) });
 
-    #[test]
-    fn test_e_zero_div_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::ZeroDivisionError"), unsafe { rb_eZeroDivError });
-    }
+        unsafe { rb_set_errinfo(Qnil) };
 
-    #[test]
-    fn test_e_not_imp_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::NotImplementedError"), unsafe { rb_eNotImpError });
+        assert.rb_nil(unsafe { rb_errinfo() });
     }
 
     #[test]
-    fn test_e_no_mem_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::NoMemoryError"), unsafe { rb_eNoMemError });
-    }
+    fn test_jump_tag(assert: &mut Assertions) {
+        extern "C" fn __test_jump_tag_raise__(_arg: VALUE) -> VALUE {
+            unsafe { rb_raise(rb_eIndexError, cstr!("__test_jump_tag__ raised")) }
+        }
 
-    #[test]
-    fn test_e_no_method_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::NoMethodError"), unsafe { rb_eNoMethodError });
-    }
+        extern "C" fn __test_jump_tag__(_self: VALUE) -> VALUE {
+            let mut state: c_int = 0;
 
-    #[test]
-    fn test_e_float_domain_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::FloatDomainError"), unsafe { rb_eFloatDomainError });
-    }
+            unsafe { rb_protect(PROTECT_FUNC::from_value(__test_jump_tag_raise__), Qnil, &mut state) };
 
-    #[test]
-    fn test_e_local_jump_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::LocalJumpError"), unsafe { rb_eLocalJumpError });
-    }
+            if state != 0 {
+                unsafe { rb_jump_tag(state) }
+            }
 
-    #[test]
-    fn test_e_system_stack_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::SystemStackError"), unsafe { rb_eSysStackError });
-    }
+            "__test_jump_tag__ did not raise".to_ruby()
+        }
 
-    #[test]
-    fn test_e_regexp_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::RegexpError"), unsafe { rb_eRegexpError });
-    }
+        unsafe {
+            rb_define_method(
+                rb_mKernel,
+                cstr!("__test_jump_tag__"),
+                ANYARGS::from_arity_1(__test_jump_tag__),
+                0
+            );
+        }
 
-    #[test]
-    fn test_e_encoding_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::EncodingError"), unsafe { rb_eEncodingError });
-    }
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_jump_tag__
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eIndexError }
+        );
 
-    #[test]
-    fn test_e_enc_compat_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Encoding::CompatibilityError"), unsafe { rb_eEncCompatError });
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_jump_tag__
+                rescue Exception => e
+                e.message
+                end
+            "#),
+            "__test_jump_tag__ raised".to_ruby()
+        );
     }
 
     #[test]
-    fn test_e_script_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::ScriptError"), unsafe { rb_eScriptError });
-    }
+    fn test_rescue(assert: &mut Assertions) {
+        let mut body = || -> VALUE { unsafe { rb_raise(rb_eArgError, cstr!("test_rescue raised")) } };
+        let mut rescue = |exception: VALUE| unsafe { intern::rb_inspect(exception) };
 
-    #[test]
-    fn test_e_name_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::NameError"), unsafe { rb_eNameError });
-    }
+        let (b_proc, data1) = unsafe { ANYARGS::from_closure_0(&mut body) };
+        let (r_proc, data2) = unsafe { ANYARGS::from_closure_1(&mut rescue) };
 
-    #[test]
-    fn test_e_syntax_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::SyntaxError"), unsafe { rb_eSyntaxError });
-    }
+        assert.rb_eq(
+            lazy_eval("ArgumentError.new('test_rescue raised').inspect"),
+            unsafe { rb_rescue(b_proc, data1, r_proc, data2) }
+        );
 
-    #[test]
-    fn test_e_load_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::LoadError"), unsafe { rb_eLoadError });
-    }
+        let mut body = || "test_rescue did not raise".to_ruby();
+        let mut rescue = |_exception: VALUE| "test_rescue rescued".to_ruby();
 
-    #[test]
-    fn test_e_math_domain_error(assert: &mut Assertions) {
-        assert.rb_eq(lazy_eval("::Math::DomainError"), unsafe { rb_eMathDomainError });
+        let (b_proc, data1) = unsafe { ANYARGS::from_closure_0(&mut body) };
+        let (r_proc, data2) = unsafe { ANYARGS::from_closure_1(&mut rescue) };
+
+        assert.rb_eq(
+            "test_rescue did not raise".to_ruby(),
+            unsafe { rb_rescue(b_proc, data1, r_proc, data2) }
+        );
     }
 
     #[test]
-    fn test_types(assert: &mut Assertions) {
-        // FIXME: Test more types, especially once we have `rb_funcall`
-        let objects = unsafe {
-            vec![
-                ("T_NONE",     T_NONE,     None), // Not sure we can test this
-                ("T_OBJECT",   T_OBJECT,   Some(intern::rb_class_new_instance(0, null(), rb_cObject))),
-                ("T_CLASS",    T_CLASS,    Some(rb_cObject)),
-                ("T_MODULE",   T_MODULE,   Some(rb_mKernel)),
-                ("T_FLOAT",    T_FLOAT,    None), // Could do this with funcall and to_f
-                ("T_STRING",   T_STRING,   Some("foo".to_ruby())),
-                ("T_REGEXP",   T_REGEXP,   Some(intern::rb_class_new_instance(1, &"[a-z]".to_ruby(), rb_cRegexp))),
-                ("T_ARRAY",    T_ARRAY,    Some(intern::rb_ary_new())),
-                ("T_HASH",     T_HASH,     Some(intern::rb_hash_new())),
-                ("T_STRUCT",   T_STRUCT,   None), // Could do this with funcall and to_f
-                ("T_BIGNUM",   T_BIGNUM,   None), // Could do this with funcall and to_i
-                ("T_FILE",     T_FILE,     None), // Need to get a file descriptor for this
-                ("T_DATA",     T_DATA,     None), // Not sure we can test this
-                ("T_MATCH",    T_MATCH,    None), // Can do this with funcall
-                ("T_COMPLEX",  T_COMPLEX,  None), // Can do this with funcall
-                ("T_RATIONAL", T_RATIONAL, None), // Can do this with funcall
-                ("T_NIL",      T_NIL,      Some(Qnil)),
-                ("T_TRUE",     T_TRUE,     Some(Qtrue)),
-                ("T_FALSE",    T_FALSE,    Some(Qfalse)),
-                ("T_SYMBOL",   T_SYMBOL,   Some(rb_id2sym(rb_intern(cstr!("foo"))))),
-                ("T_FIXNUM",   T_FIXNUM,   None), // Could do this with funcall and to_i
-                ("T_UNDEF",    T_UNDEF,    None), // Not sure we can test this
-                ("T_IMEMO",    T_IMEMO,    None), // Not sure we can test this
-                ("T_NODE",     T_NODE,     None), // Not sure we can test this
-                ("T_ICLASS",   T_ICLASS,   None), // Not sure we can test this
-                ("T_ZOMBIE",   T_ZOMBIE,   None) // Not sure we can test this
-            ]
-        };
+    fn test_rescue2(assert: &mut Assertions) {
+        extern "C" fn __test_rescue2__(_self: VALUE, class: VALUE) -> VALUE {
+            let mut body = || -> VALUE { unsafe { rb_raise(class, cstr!("__test_rescue2__ raised")) } };
+            let mut rescue = |exception: VALUE| unsafe { intern::rb_obj_class(exception) };
 
-        for (name, rtype, value) in objects.into_iter() {
-            if let Some(v) = value {
-                assert.rs_eq_msg(c_bool(unsafe { RB_TYPE_P(v, rtype) }), true, Some(format!("type of VALUE is {}", name)));
-            } else {
-                // Sanity check
-                assert.rs_eq_msg(rtype.0 >= 0x00, true, Some(format!("{} >= 0x00", name)));
-                assert.rs_eq_msg(rtype.0 < 0x1f, true, Some(format!("{} < 0x1f", name)));
-            }
+            let (b_proc, data1) = unsafe { ANYARGS::from_closure_0(&mut body) };
+            let (r_proc, data2) = unsafe { ANYARGS::from_closure_1(&mut rescue) };
+
+            unsafe { rb_rescue2(b_proc, data1, r_proc, data2, rb_eTypeError, rb_eArgError, Qfalse) }
         }
 
-        // Ensure that it doesn't only return true
-        assert.rs_eq(c_bool(unsafe { RB_TYPE_P("foo".to_ruby(), T_HASH) }), false);
-    }
+        unsafe {
+            rb_define_method(
+                rb_mKernel,
+                cstr!("__test_rescue2__"),
+                ANYARGS::from_arity_2(__test_rescue2__),
+                1
+            );
+        }
 
-    #[test]
-    fn test_class_of(assert: &mut Assertions) {
-        assert.rb_eq(unsafe { rb_cString }, unsafe { CLASS_OF("foo".to_ruby()) });
+        assert.rb_eq(lazy_eval("__test_rescue2__(TypeError)"), unsafe { rb_eTypeError });
+        assert.rb_eq(lazy_eval("__test_rescue2__(ArgumentError)"), unsafe { rb_eArgError });
 
-        // `rb_obj_class` on a class returns the class itself, `CLASS_OF` returns the singleton class
-        assert.rb_ne(unsafe { intern::rb_obj_class(rb_cString) }, unsafe { CLASS_OF(rb_cString) });
-        assert.rb_eq(lazy_eval("String.singleton_class"), unsafe { CLASS_OF(rb_cString) });
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_rescue2__(IndexError)
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eIndexError }
+        );
     }
 
     #[test]
-    fn test_numeric_conversions(assert: &mut Assertions) {
-        let int_max = c_int::max_value();
-        let long_max = c_long::max_value();
-        let long_long_max = c_longlong::max_value();
-
-        let val = -int_max + 1;
-        assert.rb_eq(lazy_eval(&format!("{}", val)), unsafe { INT2NUM(val) });
-        assert.rs_eq(val, unsafe { NUM2INT(INT2NUM(val)) });
+    fn test_ensure(assert: &mut Assertions) {
+        let ensured = Cell::new(0);
 
-        let val = (int_max as c_uint) + 1;
-        assert.rb_eq(lazy_eval(&format!("{}", val)), unsafe { UINT2NUM(val) });
-        assert.rs_eq(val, unsafe { NUM2UINT(UINT2NUM(val)) });
-
-        let val = -long_max + 1;
-        assert.rb_eq(lazy_eval(&format!("{}", val)), unsafe { LONG2NUM(val) });
-        assert.rs_eq(val, unsafe { NUM2LONG(LONG2NUM(val)) });
+        let mut body = || "test_ensure body".to_ruby();
+        let mut ensure = || { ensured.set(ensured.get() + 1); unsafe { Qnil } };
 
-        let val = (long_max as c_ulong) + 1;
-        assert.rb_eq(lazy_eval(&format!("{}", val)), unsafe { ULONG2NUM(val) });
-        assert.rs_eq(val, unsafe { NUM2ULONG(ULONG2NUM(val)) });
+        let (b_proc, data1) = unsafe { ANYARGS::from_closure_0(&mut body) };
+        let (e_proc, data2) = unsafe { ANYARGS::from_closure_0(&mut ensure) };
 
-        let val = -long_long_max + 1;
-        assert.rb_eq(lazy_eval(&format!("{}", val)), unsafe { LL2NUM(val) });
-        assert.rs_eq(val, unsafe { NUM2LL(LL2NUM(val)) });
+        assert.rb_eq("test_ensure body".to_ruby(), unsafe { rb_ensure(b_proc, data1, e_proc, data2) });
+        assert.rs_eq(ensured.get(), 1);
 
-        let val = (long_long_max as c_ulonglong) + 1;
-        assert.rb_eq(lazy_eval(&format!("{}", val)), unsafe { ULL2NUM(val) });
-        assert.rs_eq(val, unsafe { NUM2ULL(ULL2NUM(val)) });
-    }
+        let mut protected = || {
+            let mut body = || -> VALUE { unsafe { rb_raise(rb_eRuntimeError, cstr!("test_ensure raised")) } };
+            let mut ensure = || { ensured.set(ensured.get() + 1); unsafe { Qnil } };
 
-    #[test]
-    fn test_rstring_len(assert: &mut Assertions) {
-        assert.rs_eq(unsafe { RSTRING_LEN("".to_ruby()) }, 0);
-        assert.rs_eq(unsafe { RSTRING_LEN("foo".to_ruby()) }, 3);
-        assert.rs_eq(unsafe { RSTRING_LEN("this is longer".to_ruby()) }, 14);
-        assert.rs_eq(unsafe { RSTRING_LEN("☠️".to_ruby()) }, 6);
-    }
+            let (b_proc, data1) = unsafe { ANYARGS::from_closure_0(&mut body) };
+            let (e_proc, data2) = unsafe { ANYARGS::from_closure_0(&mut ensure) };
 
-    #[test]
-    fn test_rstring_ptr(assert: &mut Assertions) {
-        let cstr = unsafe { CStr::from_ptr(RSTRING_PTR("foobar".to_ruby()) as *const c_char) };
-        let unicode = unsafe { CStr::from_ptr(RSTRING_PTR("☠️".to_ruby()) as *const c_char) };
-        assert.rs_eq(cstr.to_string_lossy(), "foobar");
-        assert.rs_eq(unicode.to_string_lossy(), "☠️");
-    }
+            unsafe { rb_ensure(b_proc, data1, e_proc, data2) }
+        };
 
-    #[test]
-    fn test_rarray_len(assert: &mut Assertions) {
-        let array = unsafe { intern::rb_ary_new() };
+        let (func, arg) = unsafe { PROTECT_FUNC::from_closure(&mut protected) };
+        let mut state: c_int = 0;
 
-        assert.rs_eq(unsafe { RARRAY_LEN(array) }, 0);
+        unsafe { rb_protect(func, arg, &mut state) };
 
-        unsafe { intern::rb_ary_push(array, "foo".to_ruby()); }
-        assert.rs_eq(unsafe { RARRAY_LEN(array) }, 1);
+        assert.rs_ne(state, 0);
+        assert.rs_eq(ensured.get(), 2);
+        assert.rb_eq(unsafe { rb_eRuntimeError }, unsafe { intern::rb_obj_class(rb_errinfo()) });
 
-        unsafe { intern::rb_ary_push(array, "bar".to_ruby()); }
-        assert.rs_eq(unsafe { RARRAY_LEN(array) }, 2);
+        unsafe { rb_set_errinfo(Qnil) };
     }
 
     #[test]
-    fn test_rhash_size(assert: &mut Assertions) {
-        let hash = unsafe { intern::rb_hash_new() };
-
-        assert.rs_eq(unsafe { RHASH_SIZE(hash) }, 0);
+    fn test_funcall(assert: &mut Assertions) {
+        let string = "foo".to_ruby();
 
-        unsafe { intern::rb_hash_aset(hash, "foo".to_ruby(), "bar".to_ruby()); }
-        assert.rs_eq(unsafe { RHASH_SIZE(hash) }, 1);
+        assert.rb_eq(lazy_eval("'FOO'"), unsafe { rb_funcall!(string, rb_intern(cstr!("upcase"))) });
+        assert.rb_eq(lazy_eval("'**foo**'"), unsafe { rb_funcall!(string, rb_intern(cstr!("center")), INT2NUM(7), "*".to_ruby()) });
+        assert.rb_eq(lazy_eval("'foo'"), string);
 
-        unsafe { intern::rb_hash_aset(hash, "baz".to_ruby(), "qux".to_ruby()); }
-        assert.rs_eq(unsafe { RHASH_SIZE(hash) }, 2);
+        assert.rb_eq(lazy_eval("3"), unsafe { rb_funcall(INT2NUM(1), rb_intern(cstr!("+")), 1, INT2NUM(2)) });
     }
 
     #[test]
-    fn test_id(assert: &mut Assertions) {
-        let foo1 = unsafe { rb_intern(cstr!("foo")) };
-        let foo2 = unsafe { rb_intern(CString::new("foo").unwrap().into_raw()) };
-        let foo3 = unsafe { rb_intern2("foobar".as_ptr() as *const c_char, 3 as c_long) };
-        let foo4 = unsafe { rb_intern_str("foo".to_ruby()) };
+    fn test_funcallv(assert: &mut Assertions) {
+        let args = [unsafe { INT2NUM(2) }];
 
-        let bar = unsafe { rb_intern(cstr!("bar")) };
+        assert.rb_eq(lazy_eval("3"), unsafe { rb_funcallv(INT2NUM(1), rb_intern(cstr!("+")), 1, args.as_ptr()) });
+        assert.rb_eq(lazy_eval("'FOO'"), unsafe { rb_funcallv("foo".to_ruby(), rb_intern(cstr!("upcase")), 0, null()) });
 
-        assert.rs_eq(foo1, foo1);
-        assert.rs_eq(foo1, foo2);
-        assert.rs_eq(foo1, foo3);
-        assert.rs_eq(foo1, foo4);
+        // `Kernel#format` is private
+        let args = ["%05d".to_ruby(), unsafe { INT2NUM(42) }];
 
-        assert.rs_ne(foo1, bar);
-        assert.rs_ne(foo2, bar);
-        assert.rs_ne(foo3, bar);
-        assert.rs_ne(foo4, bar);
+        assert.rb_eq(lazy_eval("'00042'"), unsafe { rb_funcallv(rb_cObject, rb_intern(cstr!("format")), 2, args.as_ptr()) });
+    }
 
-        assert.rs_eq(foo1, unsafe { rb_sym2id(rb_id2sym(foo1)) });
-        assert.rs_eq(foo2, unsafe { rb_sym2id(rb_id2sym(foo2)) });
-        assert.rs_eq(foo3, unsafe { rb_sym2id(rb_id2sym(foo3)) });
-        assert.rs_eq(foo4, unsafe { rb_sym2id(rb_id2sym(foo4)) });
+    #[test]
+    fn test_funcallv_public(assert: &mut Assertions) {
+        extern "C" fn __test_funcallv_public__(_self: VALUE, recv: VALUE, name: VALUE) -> VALUE {
+            unsafe { rb_funcallv_public(recv, rb_sym2id(name), 0, null()) }
+        }
 
-        assert.rs_ne(foo1, unsafe { rb_sym2id(rb_id2sym(bar)) });
-        assert.rs_ne(foo2, unsafe { rb_sym2id(rb_id2sym(bar)) });
-        assert.rs_ne(foo3, unsafe { rb_sym2id(rb_id2sym(bar)) });
-        assert.rs_ne(foo4, unsafe { rb_sym2id(rb_id2sym(bar)) });
+        unsafe {
+            rb_define_method(
+                rb_mKernel,
+                cstr!("__test_funcallv_public__"),
+                ANYARGS::from_arity_3(__test_funcallv_public__),
+                2
+            );
+        }
 
-        assert.rb_eq(lazy_eval(":foo"), unsafe { rb_id2sym(foo1) });
-        assert.rb_eq(lazy_eval(":foo"), unsafe { rb_id2sym(foo2) });
-        assert.rb_eq(lazy_eval(":foo"), unsafe { rb_id2sym(foo3) });
-        assert.rb_eq(lazy_eval(":foo"), unsafe { rb_id2sym(foo4) });
-        assert.rb_eq(lazy_eval(":bar"), unsafe { rb_id2sym(bar) });
+        assert.rb_eq(lazy_eval("__test_funcallv_public__('foo', :upcase)"), "FOO".to_ruby());
 
-        assert.rb_eq(lazy_eval("'foo'"), unsafe { rb_id2str(foo1) });
-        assert.rb_eq(lazy_eval("'foo'"), unsafe { rb_id2str(foo2) });
-        assert.rb_eq(lazy_eval("'foo'"), unsafe { rb_id2str(foo3) });
-        assert.rb_eq(lazy_eval("'foo'"), unsafe { rb_id2str(foo4) });
-        assert.rb_eq(lazy_eval("'bar'"), unsafe { rb_id2str(bar) });
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_funcallv_public__(Object.new, :initialize)
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eNoMethodError }
+        );
     }
 
     #[test]
-    fn test_define_class(assert: &mut Assertions) {
-        let foo = unsafe { rb_define_class(cstr!("TestDefineClass__Foo"), rb_cObject) };
-        let bar = unsafe { rb_define_class(cstr!("TestDefineClass__Bar"), foo) };
-
-        assert.rb_eq(lazy_eval("::TestDefineClass__Foo"), foo);
-        assert.rb_eq(lazy_eval("::TestDefineClass__Bar"), bar);
+    fn test_funcall_passing_block(assert: &mut Assertions) {
+        extern "C" fn __test_funcall_passing_block__(_self: VALUE, ary: VALUE) -> VALUE {
+            unsafe { rb_funcall_passing_block(ary, rb_intern(cstr!("map")), 0, null()) }
+        }
 
-        assert.rb_eq(lazy_eval("::TestDefineClass__Foo.class"), unsafe { rb_cClass });
-        assert.rb_eq(lazy_eval("::TestDefineClass__Bar.class"), unsafe { rb_cClass });
+        unsafe {
+            rb_define_method(
+                rb_mKernel,
+                cstr!("__test_funcall_passing_block__"),
+                ANYARGS::from_arity_2(__test_funcall_passing_block__),
+                1
+            );
+        }
 
-        assert.rb_eq(lazy_eval("::TestDefineClass__Foo.superclass"), unsafe { rb_cObject });
-        assert.rb_eq(lazy_eval("::TestDefineClass__Bar.superclass"), foo);
+        assert.rb_eq(
+            lazy_eval("__test_funcall_passing_block__([1, 2, 3]) { |i| i * 2 }.inspect"),
+            "[2, 4, 6]".to_ruby()
+        );
     }
 
     #[test]
-    fn test_define_method(assert: &mut Assertions) {
-        extern "C" fn __test_define_method_arity_0__(_self: VALUE) -> VALUE {
-            "__test_define_method_arity_0__ works!".to_ruby()
+    fn test_funcall_with_block(assert: &mut Assertions) {
+        extern "C" fn __test_funcall_with_block__(_self: VALUE, ary: VALUE, block: VALUE) -> VALUE {
+            unsafe { rb_funcall_with_block(ary, rb_intern(cstr!("map")), 0, null(), block) }
         }
 
         unsafe {
             rb_define_method(
-                rb_cObject,
-                cstr!("__test_define_method_arity_0__"),
-                ANYARGS::from_arity_1(__test_define_method_arity_0__),
-                0
+                rb_mKernel,
+                cstr!("__test_funcall_with_block__"),
+                ANYARGS::from_arity_3(__test_funcall_with_block__),
+                2
             );
         }
 
         assert.rb_eq(
-            lazy_eval("::Object.new.__test_define_method_arity_0__"),
-            "__test_define_method_arity_0__ works!".to_ruby()
+            lazy_eval("__test_funcall_with_block__([1, 2, 3], proc { |i| i + 1 }).inspect"),
+            "[2, 3, 4]".to_ruby()
         );
 
-        extern "C" fn __test_define_method_arity_3__(_self: VALUE, foo_sym: VALUE, bar_sym: VALUE, baz_sym: VALUE) -> VALUE {
-            if unsafe { rb_sym2id(foo_sym) != rb_intern(cstr!("foo")) } {
-                "__test_define_method_arity_3__ failed (expected :foo for first argument)".to_ruby()
-            } else if unsafe { rb_sym2id(bar_sym) != rb_intern(cstr!("bar")) } {
-                "__test_define_method_arity_3__ failed (expected :bar for second argument)".to_ruby()
-            } else if unsafe { rb_sym2id(baz_sym) != rb_intern(cstr!("baz")) } {
-                "__test_define_method_arity_3__ failed (expected :baz for third argument)".to_ruby()
-            } else {
-                "__test_define_method_arity_3__ works!".to_ruby()
+        assert.rb_eq(
+            lazy_eval("__test_funcall_with_block__([1, 2, 3], :to_s).inspect"),
+            r#"["1", "2", "3"]"#.to_ruby()
+        );
+    }
+
+    #[test]
+    fn test_define_method_argc_argv(assert: &mut Assertions) {
+        extern "C" fn __test_define_method_argc_argv__(argc: c_int, argv: *const VALUE, _self: VALUE) -> VALUE {
+            let args = if argc == 0 { &[] } else { unsafe { slice::from_raw_parts(argv, argc as usize) } };
+            let ary = unsafe { intern::rb_ary_new() };
+
+            unsafe { intern::rb_ary_push(ary, INT2NUM(argc)) };
+
+            for arg in args {
+                unsafe { intern::rb_ary_push(ary, *arg) };
             }
+
+            ary
         }
 
         unsafe {
-            rb_define_method(
+            define_method(
                 rb_cObject,
-                cstr!("__test_define_method_arity_3__"),
-                ANYARGS::from_arity_4(__test_define_method_arity_3__),
-                3
+                cstr!("__test_define_method_argc_argv__"),
+                METHOD_FUNC::argc_argv(__test_define_method_argc_argv__)
             );
         }
 
-        assert.rb_eq(
-            lazy_eval("::Object.new.__test_define_method_arity_3__(:foo, :bar, :baz)"),
-            "__test_define_method_arity_3__ works!".to_ruby()
-        );
+        assert.rb_eq(lazy_eval("Object.instance_method(:__test_define_method_argc_argv__).arity"), unsafe { INT2NUM(-1) });
+        assert.rb_eq(lazy_eval("Object.new.__test_define_method_argc_argv__.inspect"), "[0]".to_ruby());
+        assert.rb_eq(lazy_eval("Object.new.__test_define_method_argc_argv__(:foo, 'bar').inspect"), r#"[2, :foo, "bar"]"#.to_ruby());
     }
 
     #[test]
-    fn test_define_module_function(assert: &mut Assertions) {
-        extern "C" fn __test_define_module_function_arity_0__(_self: VALUE) -> VALUE {
-            "__test_define_module_function_arity_0__ works!".to_ruby()
+    fn test_define_method_args_array(assert: &mut Assertions) {
+        extern "C" fn __test_define_method_args_array__(_self: VALUE, args: VALUE) -> VALUE {
+            args
         }
 
         unsafe {
-            rb_define_module_function(
-                rb_mKernel,
-                cstr!("__test_define_module_function_arity_0__"),
-                ANYARGS::from_arity_1(__test_define_module_function_arity_0__),
-                0
+            define_method(
+                rb_cObject,
+                cstr!("__test_define_method_args_array__"),
+                METHOD_FUNC::args_array(__test_define_method_args_array__)
             );
         }
 
-        assert.rb_eq(
-            lazy_eval("::Kernel.__test_define_module_function_arity_0__"),
-            "__test_define_module_function_arity_0__ works!".to_ruby()
-        );
+        assert.rb_eq(lazy_eval("Object.instance_method(:__test_define_method_args_array__).arity"), unsafe { INT2NUM(-1) });
+        assert.rb_eq(lazy_eval("Object.new.__test_define_method_args_array__.inspect"), "[]".to_ruby());
+        assert.rb_eq(lazy_eval("Object.new.__test_define_method_args_array__(:foo, 'bar').inspect"), r#"[:foo, "bar"]"#.to_ruby());
+    }
 
-        assert.rb_eq(
-            lazy_eval("__test_define_module_function_arity_0__"),
-            "__test_define_module_function_arity_0__ works!".to_ruby()
-        );
+    #[test]
+    fn test_define_method_typed_arity(assert: &mut Assertions) {
+        extern "C" fn __test_define_method_typed_arity_2__(_self: VALUE, foo: VALUE, bar: VALUE) -> VALUE {
+            unsafe { rb_funcall!(foo, rb_intern(cstr!("+")), bar) }
+        }
 
-        extern "C" fn __test_define_module_function_arity_3__(_self: VALUE, foo_sym: VALUE, bar_sym: VALUE, baz_sym: VALUE) -> VALUE {
-            if unsafe { rb_sym2id(foo_sym) != rb_intern(cstr!("foo")) } {
-                "__test_define_module_function_arity_3__ failed (expected :foo for first argument)".to_ruby()
-            } else if unsafe { rb_sym2id(bar_sym) != rb_intern(cstr!("bar")) } {
-                "__test_define_module_function_arity_3__ failed (expected :bar for second argument)".to_ruby()
-            } else if unsafe { rb_sym2id(baz_sym) != rb_intern(cstr!("baz")) } {
-                "__test_define_module_function_arity_3__ failed (expected :baz for third argument)".to_ruby()
-            } else {
-                "__test_define_module_function_arity_3__ works!".to_ruby()
+        extern "C" fn __test_define_singleton_method_typed__(argc: c_int, _argv: *const VALUE, _self: VALUE) -> VALUE {
+            unsafe { INT2NUM(argc) }
+        }
+
+        extern "C" fn __test_define_module_function_typed__(_self: VALUE) -> VALUE {
+            "__test_define_module_function_typed__ works!".to_ruby()
+        }
+
+        unsafe {
+            define_method(
+                rb_cObject,
+                cstr!("__test_define_method_typed_arity_2__"),
+                METHOD_FUNC::arity_2(__test_define_method_typed_arity_2__)
+            );
+
+            define_singleton_method(
+                rb_cObject,
+                cstr!("__test_define_singleton_method_typed__"),
+                METHOD_FUNC::argc_argv(__test_define_singleton_method_typed__)
+            );
+
+            define_module_function(
+                rb_mKernel,
+                cstr!("__test_define_module_function_typed__"),
+                METHOD_FUNC::arity_0(__test_define_module_function_typed__)
+            );
+        }
+
+        assert.rb_eq(lazy_eval("Object.new.__test_define_method_typed_arity_2__(1, 2)"), unsafe { INT2NUM(3) });
+        assert.rb_eq(lazy_eval("Object.__test_define_singleton_method_typed__(1, 2, 3)"), unsafe { INT2NUM(3) });
+        assert.rb_eq(lazy_eval("Kernel.__test_define_module_function_typed__"), "__test_define_module_function_typed__ works!".to_ruby());
+    }
+
+    #[test]
+    fn test_scan_args_c(assert: &mut Assertions) {
+        extern "C" fn __test_scan_args_c__(argc: c_int, argv: *const VALUE, _self: VALUE) -> VALUE {
+            let mut leading = unsafe { Qundef };
+            let mut optional = unsafe { Qundef };
+            let mut splat = unsafe { Qundef };
+
+            unsafe {
+                rb_scan_args(argc, argv, cstr!("11*"), &mut leading as *mut VALUE, &mut optional as *mut VALUE, &mut splat as *mut VALUE);
+                rb_funcall!(intern::rb_ary_new(), rb_intern(cstr!("push")), leading, optional, splat)
             }
         }
 
         unsafe {
-            rb_define_method(
+            define_method(
                 rb_mKernel,
-                cstr!("__test_define_module_function_arity_3__"),
-                ANYARGS::from_arity_4(__test_define_module_function_arity_3__),
-                3
+                cstr!("__test_scan_args_c__"),
+                METHOD_FUNC::argc_argv(__test_scan_args_c__)
             );
         }
 
-        assert.rb_eq(
-            lazy_eval("::Kernel.__test_define_module_function_arity_3__(:foo, :bar, :baz)"),
-            "__test_define_module_function_arity_3__ works!".to_ruby()
-        );
+        assert.rb_eq(lazy_eval("__test_scan_args_c__(1).inspect"), "[1, nil, []]".to_ruby());
+        assert.rb_eq(lazy_eval("__test_scan_args_c__(1, 2, 3, 4).inspect"), "[1, 2, [3, 4]]".to_ruby());
 
         assert.rb_eq(
-            lazy_eval("__test_define_module_function_arity_3__(:foo, :bar, :baz)"),
-            "__test_define_module_function_arity_3__ works!".to_ruby()
+            lazy_eval(r#"
+                begin
+                __test_scan_args_c__
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eArgError }
         );
     }
 
     #[test]
-    fn test_undef_method(assert: &mut Assertions) {
-        let class = unsafe {
-            rb_define_class(
-                cstr!("TestUndefClass"),
-                rb_cObject
-            )
-        };
+    fn test_get_kwargs(assert: &mut Assertions) {
+        let table = unsafe { [rb_intern(cstr!("foo")), rb_intern(cstr!("bar")), rb_intern(cstr!("baz"))] };
+        let mut values = unsafe { [Qnil; 3] };
 
-        unsafe { rb_undef_method(class, cstr!("to_s")) };
+        let hash = unsafe { intern::rb_hash_new() };
+        unsafe { intern::rb_hash_aset(hash, rb_id2sym(table[0]), INT2NUM(1)) };
+        unsafe { intern::rb_hash_aset(hash, rb_id2sym(table[2]), INT2NUM(3)) };
 
-        assert.rb_eq(
-            lazy_eval("::TestUndefClass.new.respond_to?(:to_s)"),
-            unsafe { Qfalse }
-        );
+        let found = unsafe { rb_get_kwargs(hash, table.as_ptr(), 1, 2, values.as_mut_ptr()) };
+
+        assert.rs_eq(2, found);
+        assert.rb_eq(unsafe { INT2NUM(1) }, values[0]);
+        assert.rs_eq(unsafe { Qundef }, values[1]);
+        assert.rb_eq(unsafe { INT2NUM(3) }, values[2]);
+        assert.rb_eq(lazy_eval("{}"), hash);
+    }
+
+    #[test]
+    fn test_extract_keywords(assert: &mut Assertions) {
+        let mut hash = unsafe { intern::rb_hash_new() };
+        unsafe { intern::rb_hash_aset(hash, rb_id2sym(rb_intern(cstr!("foo"))), INT2NUM(1)) };
+        unsafe { intern::rb_hash_aset(hash, "bar".to_ruby(), INT2NUM(2)) };
+
+        let keywords = unsafe { rb_extract_keywords(&mut hash) };
+
+        assert.rb_eq(lazy_eval("{ foo: 1 }"), keywords);
+        assert.rb_eq(lazy_eval("{ 'bar' => 2 }"), hash);
+
+        let mut hash = unsafe { intern::rb_hash_new() };
+        unsafe { intern::rb_hash_aset(hash, "bar".to_ruby(), INT2NUM(2)) };
+
+        let keywords = unsafe { rb_extract_keywords(&mut hash) };
+
+        assert.rb_eq(unsafe { Qfalse }, keywords);
+
+        let empty = unsafe { intern::rb_hash_new() };
+        let mut hash = empty;
+
+        let keywords = unsafe { rb_extract_keywords(&mut hash) };
+
+        assert.rs_eq(empty, keywords);
+        assert.rb_eq(unsafe { Qfalse }, hash);
+    }
+
+    #[test]
+    fn test_keyword_given_p(assert: &mut Assertions) {
+        #[cfg(feature = "ruby_2_7")]
+        {
+            extern "C" fn __test_keyword_given_p__(_argc: c_int, _argv: *const VALUE, _self: VALUE) -> VALUE {
+                if unsafe { rb_keyword_given_p() != 0 } {
+                    unsafe { Qtrue }
+                } else {
+                    unsafe { Qfalse }
+                }
+            }
+
+            unsafe {
+                define_method(
+                    rb_mKernel,
+                    cstr!("__test_keyword_given_p__"),
+                    METHOD_FUNC::argc_argv(__test_keyword_given_p__)
+                );
+            }
+
+            assert.rb_eq(lazy_eval("__test_keyword_given_p__(foo: 1)"), unsafe { Qtrue });
+            assert.rb_eq(lazy_eval("__test_keyword_given_p__({ foo: 1 })"), unsafe { Qfalse });
+            assert.rb_eq(lazy_eval("__test_keyword_given_p__"), unsafe { Qfalse });
+        }
+
+        #[cfg(not(feature = "ruby_2_7"))]
+        let _ = assert;
+    }
+
+    #[test]
+    fn test_yield(assert: &mut Assertions) {
+        extern "C" fn __test_yield__(_self: VALUE, value: VALUE) -> VALUE {
+            unsafe { rb_yield(value) }
+        }
+
+        extern "C" fn __test_yield_values2__(argc: c_int, argv: *const VALUE, _self: VALUE) -> VALUE {
+            unsafe { rb_yield_values2(argc, argv) }
+        }
+
+        extern "C" fn __test_yield_splat__(_self: VALUE, values: VALUE) -> VALUE {
+            unsafe { rb_yield_splat(values) }
+        }
+
+        unsafe {
+            define_method(rb_mKernel, cstr!("__test_yield__"), METHOD_FUNC::arity_1(__test_yield__));
+            define_method(rb_mKernel, cstr!("__test_yield_values2__"), METHOD_FUNC::argc_argv(__test_yield_values2__));
+            define_method(rb_mKernel, cstr!("__test_yield_splat__"), METHOD_FUNC::arity_1(__test_yield_splat__));
+        }
+
+        assert.rb_eq(lazy_eval("__test_yield__(1) { |x| x + 1 }"), unsafe { INT2NUM(2) });
+        assert.rb_eq(lazy_eval("__test_yield_values2__(1, 2, 3) { |a, b, c| [c, b, a] }.inspect"), "[3, 2, 1]".to_ruby());
+        assert.rb_eq(lazy_eval("__test_yield_values2__ { |*args| args }.inspect"), "[]".to_ruby());
+        assert.rb_eq(lazy_eval("__test_yield_splat__([1, 2]) { |a, b| a + b }"), unsafe { INT2NUM(3) });
 
         assert.rb_eq(
             lazy_eval(r#"
-                exception = begin
-                TestUndefClass.new.to_s
-                rescue => e
-                e
+                begin
+                __test_yield__(1)
+                rescue Exception => e
+                e.class
                 end
-
-                exception.class
             "#),
-            unsafe { rb_eNoMethodError }
+            unsafe { rb_eLocalJumpError }
         );
     }
 
     #[test]
-    fn test_undef_module_method(assert: &mut Assertions) {
-        extern "C" fn __test_undef_module_method__(_self: VALUE) -> VALUE {
-            "__test_undef_module_method__ works!".to_ruby()
+    fn test_need_block(assert: &mut Assertions) {
+        extern "C" fn __test_need_block__(_self: VALUE) -> VALUE {
+            unsafe {
+                rb_need_block();
+                Qtrue
+            }
         }
 
-        unsafe {
-            rb_define_method(
-                rb_mKernel,
-                cstr!("__test_undef_module_method__"),
-                ANYARGS::from_arity_1(__test_undef_module_method__),
-                0
-            );
+        extern "C" fn __test_block_proc__(_self: VALUE) -> VALUE {
+            unsafe { intern::rb_block_proc() }
         }
 
-        // NOTE: Ideally we would test this just to make sure things are set up correctly,
-        //   but due to the lazy_eval this won't pass.
-        //
-        // assert.rb_eq(
-        //     lazy_eval("::Kernel.__test_undef_module_method__"),
-        //     "__test_undef_module_method__ works!".to_ruby()
-        // );
-
         unsafe {
-            rb_undef_method(
-                rb_mKernel,
-                cstr!("__test_undef_module_method__")
-            );
+            define_method(rb_mKernel, cstr!("__test_need_block__"), METHOD_FUNC::arity_0(__test_need_block__));
+            define_method(rb_mKernel, cstr!("__test_block_proc__"), METHOD_FUNC::arity_0(__test_block_proc__));
         }
 
+        assert.rb_eq(lazy_eval("__test_need_block__ { }"), unsafe { Qtrue });
+
         assert.rb_eq(
-            lazy_eval("::Kernel.respond_to?(:__test_undef_module_method__)"),
-            unsafe { Qfalse }
+            lazy_eval(r#"
+                begin
+                __test_need_block__
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eLocalJumpError }
+        );
+
+        assert.rb_eq(lazy_eval("__test_block_proc__ { }.class"), unsafe { rb_cProc });
+        assert.rb_eq(lazy_eval("__test_block_proc__ { |x| x * 2 }.call(21)"), unsafe { INT2NUM(42) });
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_block_proc__
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eArgError }
         );
     }
 
     #[test]
-    fn test_obj_classname(assert: &mut Assertions) {
-        let class = unsafe { CStr::from_ptr(rb_obj_classname(rb_cObject)) };
-        let module = unsafe { CStr::from_ptr(rb_obj_classname(rb_mKernel)) };
-        let instance = unsafe {
-            let i = intern::rb_class_new_instance(0, null(), rb_cObject);
-            CStr::from_ptr(rb_obj_classname(i))
+    fn test_block_call(assert: &mut Assertions) {
+        let array = unsafe { rb_funcall!(intern::rb_ary_new(), rb_intern(cstr!("push")), INT2NUM(1), INT2NUM(2), INT2NUM(3)) };
+
+        let mut sum = 0;
+        let mut each = |args: &[VALUE]| {
+            sum += unsafe { NUM2INT(args[0]) };
+            unsafe { Qnil }
         };
 
-        assert.rs_eq("Class", class.to_string_lossy());
-        assert.rs_eq("Module", module.to_string_lossy());
-        assert.rs_eq("Object", instance.to_string_lossy());
+        let (func, data) = unsafe { BLOCK_CALL_FUNC::from_closure(&mut each) };
+        let result = unsafe { rb_block_call(array, rb_intern(cstr!("each")), 0, null(), func, data) };
+
+        assert.rb_eq(array, result);
+        assert.rs_eq(6, sum);
+
+        let mut double = |args: &[VALUE]| unsafe { INT2NUM(NUM2INT(args[0]) * 2) };
+
+        let (func, data) = unsafe { BLOCK_CALL_FUNC::from_closure(&mut double) };
+        let result = unsafe { rb_block_call(array, rb_intern(cstr!("map")), 0, null(), func, data) };
+
+        assert.rb_eq(lazy_eval("[2, 4, 6]"), result);
+
+        let mut pairs = Vec::new();
+        let mut each_with_index = |args: &[VALUE]| {
+            pairs.push(args.len());
+            unsafe { Qnil }
+        };
+
+        let (func, data) = unsafe { BLOCK_CALL_FUNC::from_closure(&mut each_with_index) };
+        unsafe { rb_block_call(array, rb_intern(cstr!("each_with_index")), 0, null(), func, data) };
+
+        assert.rs_eq(vec![2, 2, 2], pairs);
     }
 
     #[test]
-    fn test_block_given_p(assert: &mut Assertions) {
-        extern "C" fn __test_block_given_p__(_self: VALUE) -> VALUE {
-            if unsafe { rb_block_given_p() != 0 } {
-                unsafe { Qtrue }
-            } else {
-                unsafe { Qfalse }
-            }
+    fn test_typed_data(assert: &mut Assertions) {
+        extern "C" fn __test_check_typeddata__(_self: VALUE, obj: VALUE) -> VALUE {
+            unsafe { INT2NUM(*(rb_check_typeddata(obj, &TEST_DATA_TYPE) as *const u32) as c_int) }
         }
 
+        let class = unsafe { rb_define_class(cstr!("TestTypedData__Foo"), rb_cObject) };
+        let data = Box::into_raw(Box::new(42u32));
+        let obj = unsafe { rb_data_typed_object_wrap(class, data as *mut c_void, &TEST_DATA_TYPE) };
+
+        assert.rs_eq(true, c_bool(unsafe { RB_TYPE_P(obj, T_DATA) }));
+        assert.rs_eq(true, c_bool(unsafe { RTYPEDDATA_P(obj) }));
+        assert.rb_eq(class, unsafe { CLASS_OF(obj) });
+        assert.rs_eq(&TEST_DATA_TYPE as *const rb_data_type_t, unsafe { RTYPEDDATA_TYPE(obj) });
+        assert.rs_eq(data as *mut c_void, unsafe { RTYPEDDATA_DATA(obj) });
+        assert.rs_eq(data as *mut c_void, unsafe { rb_check_typeddata(obj, &TEST_DATA_TYPE) });
+
+        let child = unsafe { rb_data_typed_object_wrap(class, Box::into_raw(Box::new(7u32)) as *mut c_void, &TEST_DATA_CHILD_TYPE) };
+
+        assert.rs_eq(7, unsafe { *(rb_check_typeddata(child, &TEST_DATA_TYPE) as *const u32) });
+
         unsafe {
-            rb_define_method(
+            define_method(
                 rb_mKernel,
-                cstr!("__test_block_given_p__"),
-                ANYARGS::from_arity_1(__test_block_given_p__),
-                0
+                cstr!("__test_check_typeddata__"),
+                METHOD_FUNC::arity_1(__test_check_typeddata__)
             );
         }
 
         assert.rb_eq(
-            lazy_eval("__test_block_given_p__"),
-            unsafe { Qfalse }
+            lazy_eval(r#"
+                begin
+                __test_check_typeddata__("foo")
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eTypeError }
         );
 
         assert.rb_eq(
-            lazy_eval("__test_block_given_p__ { }"),
-            unsafe { Qtrue }
+            lazy_eval(r#"
+                begin
+                __test_check_typeddata__(TestTypedData__Foo.allocate)
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eTypeError }
         );
     }
+
+    #[test]
+    fn test_typed_data_zalloc(assert: &mut Assertions) {
+        let obj = unsafe { rb_data_typed_object_zalloc(rb_cObject, 8, &TEST_DATA_ZALLOC_TYPE) };
+        let data = unsafe { rb_check_typeddata(obj, &TEST_DATA_ZALLOC_TYPE) };
+
+        assert.rs_eq(false, data.is_null());
+        assert.rs_eq(0, unsafe { *(data as *const u64) });
+    }
+
+    #[test]
+    fn test_xmalloc(assert: &mut Assertions) {
+        unsafe {
+            let ptr = ruby_xmalloc(4) as *mut u8;
+            slice::from_raw_parts_mut(ptr, 4).copy_from_slice(b"ruby");
+
+            let ptr = ruby_xrealloc(ptr as *mut c_void, 8) as *mut u8;
+            assert.rs_eq(&b"ruby"[..], slice::from_raw_parts(ptr, 4));
+            ruby_xfree(ptr as *mut c_void);
+
+            let ptr = ruby_xmalloc2(4, 2) as *mut u16;
+            slice::from_raw_parts_mut(ptr, 4).copy_from_slice(&[1, 2, 3, 4]);
+            assert.rs_eq(&[1u16, 2, 3, 4][..], slice::from_raw_parts(ptr, 4));
+            ruby_xfree(ptr as *mut c_void);
+
+            let ptr = ruby_xcalloc(4, 2) as *mut u16;
+            assert.rs_eq(&[0u16; 4][..], slice::from_raw_parts(ptr, 4));
+            ruby_xfree(ptr as *mut c_void);
+
+            ruby_xfree(null_mut());
+        }
+    }
+
+    #[test]
+    fn test_xmalloc_accounting(assert: &mut Assertions) {
+        let malloc_increase_bytes = unsafe { rb_id2sym(rb_intern(cstr!("malloc_increase_bytes"))) };
+
+        unsafe { intern::rb_gc_disable() };
+
+        let before = unsafe { intern::rb_gc_stat(malloc_increase_bytes) };
+        let ptr = unsafe { ruby_xmalloc(4096) };
+        let after = unsafe { intern::rb_gc_stat(malloc_increase_bytes) };
+        unsafe { ruby_xfree(ptr) };
+
+        unsafe { intern::rb_gc_enable() };
+
+        assert.rs_eq(true, after >= before + 4096);
+    }
+
 }
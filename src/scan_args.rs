@@ -0,0 +1,322 @@
+use super::*;
+use libc::{c_int, c_long};
+use std::iter::Peekable;
+use std::slice;
+use std::str::Chars;
+
+/// The arguments of a method, as parsed by [`scan_args`].
+///
+/// Each field is only filled in when the format asks for it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScanArgs {
+    /// The leading mandatory arguments
+    pub leading: Vec<VALUE>,
+    /// The optional arguments, `None` for those that were not given
+    pub optional: Vec<Option<VALUE>>,
+    /// The remaining arguments as an [`Array`](rb_cArray), for `*`
+    pub splat: Option<VALUE>,
+    /// The trailing mandatory arguments
+    pub trailing: Vec<VALUE>,
+    /// The keyword [`Hash`](rb_cHash), for `:`, if one was given
+    pub keywords: Option<VALUE>,
+    /// The block as a [`Proc`](rb_cProc), for `&`, if one was given
+    pub block: Option<VALUE>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Format {
+    leading: usize,
+    optional: usize,
+    splat: bool,
+    trailing: usize,
+    keywords: bool,
+    block: bool,
+}
+
+impl Format {
+    fn parse(fmt: &str) -> Result<Format, Error> {
+        fn digit(chars: &mut Peekable<Chars>) -> Option<usize> {
+            let digit = chars.peek().and_then(|c| c.to_digit(10))?;
+            chars.next();
+            Some(digit as usize)
+        }
+
+        fn flag(chars: &mut Peekable<Chars>, c: char) -> bool {
+            let found = chars.peek() == Some(&c);
+
+            if found {
+                chars.next();
+            }
+
+            found
+        }
+
+        let mut chars = fmt.chars().peekable();
+        let mut format = Format::default();
+
+        // Like `rb_scan_args`, three leading digits may only be followed by `&`
+        let mut trailing_digit = false;
+
+        if let Some(leading) = digit(&mut chars) {
+            format.leading = leading;
+
+            if let Some(optional) = digit(&mut chars) {
+                format.optional = optional;
+
+                if let Some(trailing) = digit(&mut chars) {
+                    format.trailing = trailing;
+                    trailing_digit = true;
+                }
+            }
+        }
+
+        if !trailing_digit {
+            if flag(&mut chars, '*') {
+                format.splat = true;
+
+                if let Some(trailing) = digit(&mut chars) {
+                    format.trailing = trailing;
+                }
+            }
+
+            format.keywords = flag(&mut chars, ':');
+        }
+
+        format.block = flag(&mut chars, '&');
+
+        if chars.next().is_some() {
+            let class = unsafe { rb_eArgError };
+
+            return Err(Error::new(class, format!("bad scan arg format: {}", fmt)));
+        }
+
+        Ok(format)
+    }
+
+    fn mandatory(&self) -> usize {
+        self.leading + self.trailing
+    }
+
+    fn arity_error(&self, given: usize) -> Error {
+        let mandatory = self.mandatory();
+
+        let expected = if self.splat {
+            format!("{}+", mandatory)
+        } else if self.optional > 0 {
+            format!("{}..{}", mandatory, mandatory + self.optional)
+        } else {
+            format!("{}", mandatory)
+        };
+
+        let class = unsafe { rb_eArgError };
+
+        Error::new(class, format!("wrong number of arguments (given {}, expected {})", given, expected))
+    }
+}
+
+/// Parses the arguments of a method defined with arity `-1`, like
+/// [`rb_scan_args`] but returning the result instead of writing through
+/// variadic pointers.
+///
+/// * `argc` - the `argc` passed to the method
+/// * `argv` - the `argv` passed to the method
+/// * `fmt` - a format string, as described in [`rb_scan_args`], e.g. `"12*:&"`
+/// * Returns the parsed arguments, or an [`ArgumentError`](rb_eArgError) if
+/// `fmt` is not a valid format or the number of arguments doesn't match it
+///
+/// ```ignore
+/// extern "C" fn greet(argc: c_int, argv: *const VALUE, _self: VALUE) -> VALUE {
+///     let args = match unsafe { scan_args(argc, argv, "11") } {
+///         Ok(args) => args,
+///         Err(error) => unsafe { error.raise() },
+///     };
+///
+///     let name = args.leading[0];
+///     let greeting = args.optional[0].unwrap_or_else(|| "Hello".to_ruby());
+///
+///     // ...
+/// }
+/// ```
+///
+/// # Safety
+///
+/// * `argv` must point to at least `argc` `VALUE`s
+/// * When `fmt` contains `&`, must be called from a method invoked by Ruby
+///
+/// # Miscellaneous
+///
+/// * With `:`, the last argument is taken as the keywords if it is a `Hash`
+/// and there are more arguments than mandatory ones. Unlike `rb_scan_args`,
+/// the hash is not split into `Symbol` and non-`Symbol` keys; use
+/// [`rb_extract_keywords`] for that.
+/// * The `VALUE`s are stored in `Vec`s, which the GC doesn't scan. They are
+/// kept alive by `argv` itself, except for `splat` and `block`, which are
+/// kept alive as long as the returned `ScanArgs` stays on the stack.
+pub unsafe fn scan_args(argc: c_int, argv: *const VALUE, fmt: &str) -> Result<ScanArgs, Error> {
+    let format = Format::parse(fmt)?;
+
+    let mut args = if argc > 0 { slice::from_raw_parts(argv, argc as usize) } else { &[] };
+    let mut result = ScanArgs::default();
+
+    if format.keywords && args.len() > format.mandatory() {
+        if let Some((&last, rest)) = args.split_last() {
+            if RB_TYPE_P(last, T_HASH) != 0 {
+                result.keywords = Some(last);
+                args = rest;
+            }
+        }
+    }
+
+    if args.len() < format.mandatory() || (!format.splat && args.len() > format.mandatory() + format.optional) {
+        return Err(format.arity_error(args.len()));
+    }
+
+    let (leading, rest) = args.split_at(format.leading);
+    let (rest, trailing) = rest.split_at(rest.len() - format.trailing);
+
+    result.leading = leading.to_vec();
+    result.optional = (0..format.optional).map(|i| rest.get(i).cloned()).collect();
+    result.trailing = trailing.to_vec();
+
+    if format.splat {
+        let rest = &rest[format.optional.min(rest.len())..];
+        let splat = rb_ary_new_capa(rest.len() as c_long);
+
+        for &arg in rest {
+            rb_ary_push(splat, arg);
+        }
+
+        result.splat = Some(splat);
+    }
+
+    if format.block && rb_block_given_p() != 0 {
+        result.block = Some(rb_block_proc());
+    }
+
+    Ok(result)
+}
+
+tests! {
+    use super::*;
+    use super::super::testing::{Assertions, ToRuby, lazy_eval};
+
+    fn to_array<'a, I: IntoIterator<Item = &'a VALUE>>(values: I) -> VALUE {
+        unsafe {
+            let array = rb_ary_new();
+
+            for &value in values {
+                rb_ary_push(array, value);
+            }
+
+            array
+        }
+    }
+
+    #[test]
+    fn test_scan_args_format(assert: &mut Assertions) {
+        assert.rs_eq(Ok(Format::default()), Format::parse(""));
+        assert.rs_eq(Ok(Format { leading: 1, optional: 2, splat: true, trailing: 0, keywords: true, block: true, }), Format::parse("12*:&"));
+        assert.rs_eq(Ok(Format { leading: 0, optional: 0, splat: true, trailing: 1, keywords: false, block: false, }), Format::parse("*1"));
+        assert.rs_eq(Ok(Format { leading: 1, optional: 1, splat: false, trailing: 3, keywords: false, block: true, }), Format::parse("113&"));
+        assert.rs_eq(Ok(Format { leading: 2, optional: 0, splat: false, trailing: 0, keywords: true, block: false, }), Format::parse("2:"));
+
+        let class = unsafe { rb_eArgError };
+
+        for &fmt in &["113:", "110*", "1*1*", "&:", "x"] {
+            assert.rs_eq(Err(Error::new(class, format!("bad scan arg format: {}", fmt))), Format::parse(fmt));
+        }
+    }
+
+    #[test]
+    fn test_scan_args(assert: &mut Assertions) {
+        extern "C" fn __test_scan_args__(argc: c_int, argv: *const VALUE, _self: VALUE) -> VALUE {
+            let args = match unsafe { scan_args(argc, argv, "11*1:&") } {
+                Ok(args) => args,
+                Err(error) => unsafe { error.raise() },
+            };
+
+            unsafe {
+                let optional: Vec<VALUE> = args.optional.iter().map(|arg| arg.unwrap_or(Qnil)).collect();
+                let keywords = args.keywords.map(|hash| rb_funcall!(hash, rb_intern(cstr!("to_a"))));
+                let block = args.block.map(|block| rb_funcall!(block, rb_intern(cstr!("call"))));
+
+                to_array(&[
+                    to_array(&args.leading),
+                    to_array(&optional),
+                    args.splat.unwrap_or(Qnil),
+                    to_array(&args.trailing),
+                    keywords.unwrap_or(Qnil),
+                    block.unwrap_or(Qnil),
+                ])
+            }
+        }
+
+        unsafe {
+            define_method(
+                rb_mKernel,
+                cstr!("__test_scan_args__"),
+                METHOD_FUNC::argc_argv(__test_scan_args__)
+            );
+        }
+
+        assert.rb_eq(
+            lazy_eval("__test_scan_args__(1, 2).inspect"),
+            "[[1], [nil], [], [2], nil, nil]".to_ruby()
+        );
+
+        assert.rb_eq(
+            lazy_eval("__test_scan_args__(1, 2, 3).inspect"),
+            "[[1], [2], [], [3], nil, nil]".to_ruby()
+        );
+
+        assert.rb_eq(
+            lazy_eval("__test_scan_args__(1, 2, 3, 4, 5, a: 6) { :block }.inspect"),
+            "[[1], [2], [3, 4], [5], [[:a, 6]], :block]".to_ruby()
+        );
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_scan_args__(1)
+                rescue Exception => e
+                [e.class, e.message].inspect
+                end
+            "#),
+            r#"[ArgumentError, "wrong number of arguments (given 1, expected 2+)"]"#.to_ruby()
+        );
+    }
+
+    #[test]
+    fn test_scan_args_arity_error(assert: &mut Assertions) {
+        extern "C" fn __test_scan_args_arity_error__(argc: c_int, argv: *const VALUE, _self: VALUE) -> VALUE {
+            let args = match unsafe { scan_args(argc, argv, "11") } {
+                Ok(args) => args,
+                Err(error) => unsafe { error.raise() },
+            };
+
+            unsafe { INT2NUM(args.leading.len() as c_int + args.optional.iter().filter(|arg| arg.is_some()).count() as c_int) }
+        }
+
+        unsafe {
+            define_method(
+                rb_mKernel,
+                cstr!("__test_scan_args_arity_error__"),
+                METHOD_FUNC::argc_argv(__test_scan_args_arity_error__)
+            );
+        }
+
+        assert.rb_eq(lazy_eval("__test_scan_args_arity_error__(1)"), unsafe { INT2NUM(1) });
+        assert.rb_eq(lazy_eval("__test_scan_args_arity_error__(1, 2)"), unsafe { INT2NUM(2) });
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_scan_args_arity_error__(1, 2, 3)
+                rescue Exception => e
+                [e.class, e.message].inspect
+                end
+            "#),
+            r#"[ArgumentError, "wrong number of arguments (given 3, expected 1..2)"]"#.to_ruby()
+        );
+    }
+}
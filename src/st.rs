@@ -13,7 +13,7 @@ use libc::c_int;
 #[allow(non_camel_case_types)]
 pub struct st_retval(c_int);
 
-extern {
+extern "C" {
     /// Generally used as a return value from traversing callbacks to indicate
     /// that traversing should continue.
     #[link_name = "RS_ST_CONTINUE"]
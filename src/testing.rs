@@ -4,55 +4,62 @@ use std::cmp::PartialEq;
 use std::fmt::Debug;
 
 lazy_static! {
-    static ref TESTING: VALUE = {
+    static ref TESTING: GlobalValue = {
         unsafe {
-            rb_const_get(rb_cObject, rb_intern(cstr!("Testing")))
+            const_get(rb_cObject, cstr!("Testing"))
         }
     };
 
-    static ref ASSERTIONS: VALUE = {
+    static ref ASSERTIONS: GlobalValue = {
         unsafe {
-            rb_const_get(*TESTING, rb_intern(cstr!("Assertions")))
+            const_get(TESTING.get(), cstr!("Assertions"))
         }
     };
 
-    static ref ASSERT_EQ: VALUE = {
+    static ref ASSERT_EQ: GlobalValue = {
         unsafe {
-            rb_const_get(*ASSERTIONS, rb_intern(cstr!("Equal")))
+            const_get(ASSERTIONS.get(), cstr!("Equal"))
         }
     };
 
-    static ref ASSERT_NE: VALUE = {
+    static ref ASSERT_NE: GlobalValue = {
         unsafe {
-            rb_const_get(*ASSERTIONS, rb_intern(cstr!("NotEqual")))
+            const_get(ASSERTIONS.get(), cstr!("NotEqual"))
         }
     };
 
-    static ref ASSERT_OK: VALUE = {
+    static ref ASSERT_OK: GlobalValue = {
         unsafe {
-            rb_const_get(*ASSERTIONS, rb_intern(cstr!("Ok")))
+            const_get(ASSERTIONS.get(), cstr!("Ok"))
         }
     };
 
-    static ref ASSERT_NIL: VALUE = {
+    static ref ASSERT_NIL: GlobalValue = {
         unsafe {
-            rb_const_get(*ASSERTIONS, rb_intern(cstr!("Nil")))
+            const_get(ASSERTIONS.get(), cstr!("Nil"))
         }
     };
 
-    static ref LAZY_VALUE: VALUE = {
+    static ref LAZY_VALUE: GlobalValue = {
         unsafe {
-            rb_const_get(*TESTING, rb_intern(cstr!("LazyValue")))
+            const_get(TESTING.get(), cstr!("LazyValue"))
         }
     };
 
-    pub static ref TESTS: VALUE = {
+    pub static ref TESTS: GlobalValue = {
         unsafe {
-            rb_const_get(*TESTING, rb_intern(cstr!("Tests")))
+            const_get(TESTING.get(), cstr!("Tests"))
         }
     };
 }
 
+// The handles above are cached outside of Ruby's heap, so they are registered
+// with the GC to make sure they stay alive even if the constants are removed,
+// which also pins them so `GC.compact` can't move them.
+unsafe fn const_get(class: VALUE, name: *const c_char) -> GlobalValue {
+    GlobalValue::new(rb_const_get(class, rb_intern(name)))
+}
+
 pub trait ToRuby {
     fn to_ruby(&self) -> VALUE;
 }
@@ -67,7 +74,7 @@ impl ToRuby for str {
 
 impl ToRuby for String {
     fn to_ruby(&self) -> VALUE {
-        str::to_ruby(&self)
+        str::to_ruby(self)
     }
 }
 
@@ -88,18 +95,30 @@ macro_rules! new {
     };
 }
 
+// The assertions are kept in a rooted Ruby array, as tests may run the GC
+// before returning them.
 #[derive(Debug)]
 pub struct Assertions {
-    assertions: Vec<VALUE>,
+    assertions: Rooted,
+}
+
+impl Default for Assertions {
+    fn default() -> Self {
+        Assertions::new()
+    }
 }
 
 impl Assertions {
     pub fn new() -> Self {
-        Assertions { assertions: vec![] }
+        Assertions { assertions: unsafe { Rooted::new(rb_ary_new()) } }
+    }
+
+    fn push(&mut self, assertion: VALUE) {
+        unsafe { rb_ary_push(self.assertions.get(), assertion) };
     }
 
     pub fn rb_eq(&mut self, expected: VALUE, actual: VALUE) {
-        self.assertions.push(new!(*ASSERT_EQ, expected, actual));
+        self.push(new!(ASSERT_EQ.get(), expected, actual));
     }
 
     pub fn rs_eq<T: Debug + PartialEq<U>, U: Debug + PartialEq<T>>(&mut self, lhs: T, rhs: U) {
@@ -121,11 +140,11 @@ impl Assertions {
             format!("{:?} == {:?}", lhs, rhs)
         };
 
-        self.assertions.push(new!(*ASSERT_OK, predicate, message.to_ruby()));
+        self.push(new!(ASSERT_OK.get(), predicate, message.to_ruby()));
     }
 
     pub fn rb_ne(&mut self, expected: VALUE, actual: VALUE) {
-        self.assertions.push(new!(*ASSERT_NE, expected, actual));
+        self.push(new!(ASSERT_NE.get(), expected, actual));
     }
 
     pub fn rs_ne<T: Debug + PartialEq<U>, U: Debug + PartialEq<T>>(&mut self, lhs: T, rhs: U) {
@@ -139,24 +158,17 @@ impl Assertions {
 
         let message = format!("{:?} != {:?}", lhs, rhs).to_ruby();
 
-        self.assertions.push(new!(*ASSERT_OK, predicate, message));
+        self.push(new!(ASSERT_OK.get(), predicate, message));
     }
 
     pub fn rb_nil(&mut self, value: VALUE) {
-        self.assertions.push(new!(*ASSERT_NIL, value));
+        self.push(new!(ASSERT_NIL.get(), value));
     }
 }
 
 impl ToRuby for Assertions {
     fn to_ruby(&self) -> VALUE {
-        let len = self.assertions.len() * 2;
-        let array = unsafe { rb_ary_new_capa(len as c_long) };
-
-        for assertion in &self.assertions {
-            unsafe { rb_ary_push(array, *assertion) };
-        }
-
-        array
+        self.assertions.get()
     }
 }
 
@@ -222,7 +234,7 @@ macro_rules! rb_test_items {
                     let func = $crate::ANYARGS::from_arity_1($name);
                     let arity = 0;
 
-                    unsafe { rb_define_module_function(*$crate::testing::TESTS, name, func, arity) };
+                    unsafe { rb_define_module_function($crate::testing::TESTS.get(), name, func, arity) };
                 }
             },
             rest: { $($rest)* }
@@ -282,6 +294,18 @@ macro_rules! rb_init_items {
         }
     };
 
+    (
+        items: { $($item:tt)* },
+        init: { $($init:tt)* },
+        rest: { #[cfg($cfg:meta)] mod $name:ident; $($rest:tt)* }
+    ) => {
+        rb_init_items! {
+            items: { $($item)* #[cfg($cfg)] pub mod $name; },
+            init: { $($init)* #[cfg($cfg)] $name::tests::init(); },
+            rest: { $($rest)* }
+        }
+    };
+
     (
         items: { $($item:tt)* },
         init: { $($init:tt)* },
@@ -296,7 +320,7 @@ macro_rules! rb_init_items {
 }
 
 pub fn lazy_eval(code: &str) -> VALUE {
-    new!(*LAZY_VALUE, code.to_ruby())
+    new!(LAZY_VALUE.get(), code.to_ruby())
 }
 
 pub fn c_bool(val: c_int) -> bool {
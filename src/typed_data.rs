@@ -0,0 +1,319 @@
+use super::*;
+use libc::{c_char, c_void, size_t};
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::mem::size_of;
+use std::ptr::{self, null, null_mut};
+
+/// Reports the heap memory owned by a value, for `ObjectSpace.memsize_of`.
+///
+/// Implemented by types wrapped with [`TypedData`]. Only the type knows what
+/// it owns on the heap, so without it `dsize` could only report
+/// `size_of::<T>()`. The default reports no heap memory, so
+/// `impl HeapSize for Foo {}` is enough when the exact number doesn't matter.
+pub trait HeapSize {
+    /// The number of bytes owned by `self` on the heap, not counting
+    /// `size_of::<Self>()`
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>()
+    }
+}
+
+/// Provides the [`rb_data_type_t`] of a type wrapped with [`TypedData`].
+///
+/// Rust has no generic statics, so each type needs its own `static`, which
+/// [`typed_data!`] declares: `typed_data!(Foo);` implements `DataType` for
+/// `Foo`, naming the data type `"Foo"`.
+///
+/// # Safety
+///
+/// * `data_type` must return the same `rb_data_type_t` on every call, and no
+/// other type may share it
+/// * The `rb_data_type_t` must be the one declared by [`typed_data!`], as its
+/// `dfree` and `dsize` assume the data is a `Box<Self>`
+pub unsafe trait DataType: HeapSize + Send + 'static {
+    fn data_type() -> &'static rb_data_type_t;
+}
+
+/// Implements [`DataType`] for a type, declaring its `static`
+/// [`rb_data_type_t`].
+///
+/// ```ignore
+/// struct Counter { count: u64 }
+///
+/// impl HeapSize for Counter {}
+///
+/// typed_data!(Counter);
+/// ```
+#[macro_export]
+macro_rules! typed_data {
+    ($ty:ty) => {
+        unsafe impl $crate::DataType for $ty {
+            fn data_type() -> &'static $crate::rb_data_type_t {
+                static DATA_TYPE: $crate::rb_data_type_t =
+                    $crate::TypedData::<$ty>::new_data_type(concat!(stringify!($ty), "\0"));
+
+                &DATA_TYPE
+            }
+        }
+    };
+}
+
+/// Wraps Rust values of type `T` in Ruby objects.
+///
+/// Each `T` gets its own [`rb_data_type_t`], declared with [`typed_data!`],
+/// whose `dfree` drops the value and whose `dsize` reports `size_of::<T>()`
+/// plus [`HeapSize::heap_size`].
+///
+/// ```ignore
+/// struct Counter { count: u64 }
+///
+/// impl HeapSize for Counter {}
+///
+/// typed_data!(Counter);
+///
+/// let obj = unsafe { TypedData::wrap(class, Counter { count: 0 }) };
+///
+/// match unsafe { TypedData::<Counter>::get_mut(&obj) } {
+///     Ok(counter) => counter.count += 1,
+///     Err(error) => unsafe { error.raise() },
+/// }
+/// ```
+///
+/// # Miscellaneous
+///
+/// * Values are dropped immediately when the object is collected (see
+/// [`RUBY_TYPED_FREE_IMMEDIATELY`]), so `T`'s `Drop` must not call into Ruby.
+/// * The values must not hold Ruby objects, as they are not marked.
+pub struct TypedData<T>(PhantomData<T>);
+
+impl<T: HeapSize + Send + 'static> TypedData<T> {
+    /// Builds the [`rb_data_type_t`] for `T`, for [`typed_data!`].
+    ///
+    /// * `name` - the name of the data type, ending with a nul byte
+    #[doc(hidden)]
+    pub const fn new_data_type(name: &'static str) -> rb_data_type_t {
+        rb_data_type_t {
+            wrap_struct_name: name.as_ptr() as *const c_char,
+            function: rb_data_type_function_t {
+                dmark: None,
+                dfree: Some(Self::free),
+                dsize: Some(Self::size),
+                dcompact: None,
+                reserved: [null_mut()],
+            },
+            parent: null(),
+            data: null_mut(),
+            flags: RUBY_TYPED_FREE_IMMEDIATELY,
+        }
+    }
+
+    extern "C" fn free(data: *mut c_void) {
+        if !data.is_null() {
+            unsafe { drop(Box::from_raw(data as *mut T)) }
+        }
+    }
+
+    extern "C" fn size(data: *const c_void) -> size_t {
+        if data.is_null() {
+            return 0;
+        }
+
+        let value = unsafe { &*(data as *const T) };
+        size_of::<T>() + value.heap_size()
+    }
+}
+
+impl<T: DataType> TypedData<T> {
+    /// Returns the [`rb_data_type_t`] used for `T`.
+    #[inline(always)]
+    pub fn data_type() -> &'static rb_data_type_t {
+        T::data_type()
+    }
+
+    /// Moves `value` into a new Ruby object of the given class.
+    ///
+    /// * `class` - the class of the new object
+    /// * `value` - the value to wrap, dropped when the object is collected
+    /// * Returns a new [`T_DATA`] object
+    ///
+    /// # Safety
+    ///
+    /// * `class` must be a `Class`
+    pub unsafe fn wrap(class: VALUE, value: T) -> VALUE {
+        let data = Box::into_raw(Box::new(value));
+        rb_data_typed_object_wrap(class, data as *mut c_void, Self::data_type())
+    }
+
+    /// Borrows the value wrapped by `obj`.
+    ///
+    /// * `obj` - any Ruby object
+    /// * Returns the value, or a [`TypeError`](rb_eTypeError) if `obj` does
+    /// not wrap a `T`
+    ///
+    /// # Safety
+    ///
+    /// * No [`TypedData::get_mut`] reference to the same value may be live at
+    /// the same time, including one obtained through another copy of the
+    /// `VALUE` or by Ruby code called while the borrow is held
+    ///
+    /// # Miscellaneous
+    ///
+    /// * The borrow is tied to `obj`, which keeps the object on the stack, and
+    /// so alive, for as long as the reference is used.
+    pub unsafe fn get(obj: &VALUE) -> Result<&T, Error> {
+        Self::data(*obj).map(|data| &*data)
+    }
+
+    /// Mutably borrows the value wrapped by `obj`.
+    ///
+    /// * `obj` - any Ruby object
+    /// * Returns the value, or a [`TypeError`](rb_eTypeError) if `obj` does
+    /// not wrap a `T`
+    ///
+    /// # Safety
+    ///
+    /// * No other reference to the same value may be live at the same time,
+    /// from [`TypedData::get`] or `get_mut`, including one obtained through
+    /// another copy of the `VALUE` or by Ruby code called while the borrow is
+    /// held
+    ///
+    /// # Miscellaneous
+    ///
+    /// * The borrow is tied to `obj`, which keeps the object on the stack, and
+    /// so alive, for as long as the reference is used.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn get_mut(obj: &VALUE) -> Result<&mut T, Error> {
+        Self::data(*obj).map(|data| &mut *data)
+    }
+
+    unsafe fn data(obj: VALUE) -> Result<*mut T, Error> {
+        let data_type = Self::data_type();
+
+        if RB_TYPE_P(obj, T_DATA) == 0 || RTYPEDDATA_P(obj) == 0 || !ptr::eq(RTYPEDDATA_TYPE(obj), data_type) {
+            let class = CStr::from_ptr(rb_obj_classname(obj)).to_string_lossy();
+            let expected = CStr::from_ptr(data_type.wrap_struct_name).to_string_lossy();
+
+            return Err(Error::new(rb_eTypeError, format!("wrong argument type {} (expected {})", class, expected)));
+        }
+
+        let data = RTYPEDDATA_DATA(obj) as *mut T;
+
+        if data.is_null() {
+            let expected = CStr::from_ptr(data_type.wrap_struct_name).to_string_lossy();
+
+            return Err(Error::new(rb_eTypeError, format!("uninitialized {}", expected)));
+        }
+
+        Ok(data)
+    }
+}
+
+tests! {
+    use super::*;
+    use super::super::testing::{Assertions, ToRuby, lazy_eval};
+
+    struct Counter {
+        name: String,
+        count: u32,
+    }
+
+    impl HeapSize for Counter {
+        fn heap_size(&self) -> usize {
+            self.name.heap_size()
+        }
+    }
+
+    typed_data!(Counter);
+
+    struct Other;
+
+    impl HeapSize for Other {}
+
+    typed_data!(Other);
+
+    #[test]
+    fn test_typed_data_type(assert: &mut Assertions) {
+        let data_type = TypedData::<Counter>::data_type();
+
+        assert.rs_eq(data_type as *const rb_data_type_t, TypedData::<Counter>::data_type() as *const rb_data_type_t);
+        assert.rs_ne(data_type as *const rb_data_type_t, TypedData::<Other>::data_type() as *const rb_data_type_t);
+        assert.rs_eq("Counter", unsafe { CStr::from_ptr(data_type.wrap_struct_name) }.to_str().unwrap());
+
+        let counter = Counter { name: String::with_capacity(16), count: 0 };
+        let size = (data_type.function.dsize.unwrap())(&counter as *const Counter as *const c_void);
+
+        assert.rs_eq(size_of::<Counter>() + 16, size);
+    }
+
+    #[test]
+    fn test_typed_data_get(assert: &mut Assertions) {
+        let class = unsafe { rb_define_class(cstr!("TestTypedData__Counter"), rb_cObject) };
+        let obj = unsafe { TypedData::wrap(class, Counter { name: String::from("counter"), count: 0 }) };
+
+        assert.rb_eq(class, unsafe { CLASS_OF(obj) });
+
+        unsafe { TypedData::<Counter>::get_mut(&obj).unwrap().count += 2 };
+
+        let counter = unsafe { TypedData::<Counter>::get(&obj) }.unwrap();
+
+        assert.rs_eq("counter", counter.name.as_str());
+        assert.rs_eq(2, counter.count);
+
+        let error = unsafe { TypedData::<Other>::get(&obj) }.err().unwrap();
+
+        assert.rb_eq(unsafe { rb_eTypeError }, error.class());
+        assert.rs_eq("wrong argument type TestTypedData__Counter (expected Other)", error.message());
+
+        let error = unsafe { TypedData::<Counter>::get(&"foo".to_ruby()) }.err().unwrap();
+
+        assert.rs_eq("wrong argument type String (expected Counter)", error.message());
+    }
+
+    #[test]
+    fn test_typed_data_raise(assert: &mut Assertions) {
+        extern "C" fn __test_typed_data_count__(_self: VALUE, obj: VALUE) -> VALUE {
+            let count = match unsafe { TypedData::<Counter>::get(&obj) } {
+                Ok(counter) => counter.count,
+                Err(error) => unsafe { error.raise() },
+            };
+
+            unsafe { UINT2NUM(count) }
+        }
+
+        unsafe {
+            define_method(
+                rb_mKernel,
+                cstr!("__test_typed_data_count__"),
+                METHOD_FUNC::arity_1(__test_typed_data_count__)
+            );
+        }
+
+        let obj = unsafe { TypedData::wrap(rb_cObject, Counter { name: String::new(), count: 42 }) };
+
+        assert.rb_eq(unsafe { INT2NUM(42) }, unsafe { rb_funcall!(rb_mKernel, rb_intern(cstr!("__test_typed_data_count__")), obj) });
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_typed_data_count__(Object.new)
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eTypeError }
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use super::*;
+use libc::{c_char, c_long};
+use std::any::Any;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// A Ruby exception described on the Rust side, to be raised once the Rust
+/// code that produced it is done.
+///
+/// Helpers in this crate that can fail return this rather than raising
+/// directly, so that callers get a chance to drop their own values first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Error {
+    class: VALUE,
+    message: String,
+}
+
+impl Error {
+    /// * `class` - an exception class, such as [`rb_eArgError`]
+    /// * `message` - the exception message
+    pub fn new<S: Into<String>>(class: VALUE, message: S) -> Self {
+        Error { class, message: message.into() }
+    }
+
+    pub fn class(&self) -> VALUE {
+        self.class
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Raises this error as a Ruby exception.
+    ///
+    /// The message is copied into the exception and dropped before raising.
+    ///
+    /// # Safety
+    ///
+    /// * The call unwinds via `longjmp`, so destructors of any other Rust
+    /// values still on the stack will not run. Drop them before raising.
+    pub unsafe fn raise(self) -> ! {
+        let Error { class, message } = self;
+        let exception = rb_exc_new(class, message.as_ptr() as *const c_char, message.len() as c_long);
+
+        drop(message);
+
+        rb_exc_raise(exception)
+    }
+}
+
+/// Calls `func`, converting a Rust panic into a Ruby exception.
+///
+/// If `func` panics, the panic is caught and an exception of the given class
+/// is raised in its place, carrying the panic message. Otherwise, the result
+/// of `func` is returned.
+///
+/// * `class` - an exception class, such as [`rb_eRuntimeError`]
+/// * `func` - the code to run
+///
+/// This is what [`rb_method!`](macro.rb_method.html) expands to, and is
+/// useful on its own for any `extern "C"` function that Ruby calls into.
+///
+/// # Safety
+///
+/// * Raising unwinds via `longjmp`, so the caller must not hold values with
+/// destructors across this call. Everything owned by `func` has already
+/// been dropped by the time the exception is raised.
+/// * Must be called from a function invoked by Ruby, so that there is a
+/// Ruby frame to unwind to.
+pub unsafe fn rescue_panic<F: FnOnce() -> VALUE>(class: VALUE, func: F) -> VALUE {
+    match catch_unwind(AssertUnwindSafe(func)) {
+        Ok(value) => value,
+        Err(payload) => Error::new(class, panic_message(payload)).raise(),
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&'static str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("Rust panic")
+    }
+}
+
+/// Defines an `extern "C"` function whose panics are re-raised as Ruby
+/// exceptions, suitable for [`rb_define_method`] and friends.
+///
+/// Unwinding out of an `extern "C"` function aborts the process, so any
+/// function registered with Ruby that might panic should be defined with this
+/// macro. The exception class defaults to [`RuntimeError`](rb_eRuntimeError)
+/// and can be changed with a leading `#[raise(...)]` attribute.
+///
+/// The expression in `#[raise(...)]` is evaluated in a safe context, so
+/// reading one of the `rb_e*` statics requires an `unsafe` block.
+///
+/// ```ignore
+/// rb_method! {
+///     #[raise(unsafe { rb_eArgError })]
+///     fn parse(_self: VALUE, input: VALUE) -> VALUE {
+///         // ...
+///     }
+/// }
+///
+/// rb_define_method(class, cstr!("parse"), ANYARGS::from_arity_2(parse), 1);
+/// ```
+///
+/// See [`rescue_panic`] for details.
+#[macro_export]
+macro_rules! rb_method {
+    (
+        #[raise($class:expr)]
+        $(#[$attr:meta])*
+        $vis:vis fn $name:ident($($arg:ident : $ty:ty),*) -> VALUE $body:block
+    ) => {
+        $(#[$attr])*
+        $vis extern "C" fn $name($($arg: $ty),*) -> $crate::VALUE {
+            let class = $class;
+
+            // Built outside of the `unsafe` block, which would otherwise
+            // extend to `$body`
+            let func = move || $body;
+
+            unsafe { $crate::rescue_panic(class, func) }
+        }
+    };
+
+    (
+        $(#[$attr:meta])*
+        $vis:vis fn $name:ident($($arg:ident : $ty:ty),*) -> VALUE $body:block
+    ) => {
+        $(#[$attr])*
+        $vis extern "C" fn $name($($arg: $ty),*) -> $crate::VALUE {
+            let class = unsafe { $crate::rb_eRuntimeError };
+
+            // Built outside of the `unsafe` block, which would otherwise
+            // extend to `$body`
+            let func = move || $body;
+
+            unsafe { $crate::rescue_panic(class, func) }
+        }
+    };
+}
+
+tests! {
+    use super::*;
+    use super::super::testing::{Assertions, ToRuby, lazy_eval};
+
+    #[test]
+    fn test_error_raise(assert: &mut Assertions) {
+        extern "C" fn __test_error_raise__(_self: VALUE) -> VALUE {
+            let error = Error::new(unsafe { rb_eKeyError }, format!("{} raised", "__test_error_raise__"));
+            unsafe { error.raise() }
+        }
+
+        let error = Error::new(unsafe { rb_eKeyError }, "message");
+
+        assert.rb_eq(unsafe { rb_eKeyError }, error.class());
+        assert.rs_eq("message", error.message());
+
+        unsafe {
+            rb_define_method(
+                rb_mKernel,
+                cstr!("__test_error_raise__"),
+                ANYARGS::from_arity_1(__test_error_raise__),
+                0
+            );
+        }
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_error_raise__
+                rescue Exception => e
+                [e.class, e.message].inspect
+                end
+            "#),
+            r#"[KeyError, "__test_error_raise__ raised"]"#.to_ruby()
+        );
+    }
+
+    #[test]
+    fn test_rb_method(assert: &mut Assertions) {
+        rb_method! {
+            fn __test_rb_method_ok__(_self: VALUE, arg: VALUE) -> VALUE {
+                arg
+            }
+        }
+
+        rb_method! {
+            fn __test_rb_method_panic__(_self: VALUE) -> VALUE {
+                panic!("__test_rb_method_panic__ panicked")
+            }
+        }
+
+        rb_method! {
+            #[raise(unsafe { rb_eArgError })]
+            fn __test_rb_method_panic_arg_error__(_self: VALUE, arg: VALUE) -> VALUE {
+                let arg = unsafe { NUM2INT(arg) };
+                panic!("__test_rb_method_panic_arg_error__ panicked with {}", arg)
+            }
+        }
+
+        unsafe {
+            rb_define_method(
+                rb_mKernel,
+                cstr!("__test_rb_method_ok__"),
+                ANYARGS::from_arity_2(__test_rb_method_ok__),
+                1
+            );
+
+            rb_define_method(
+                rb_mKernel,
+                cstr!("__test_rb_method_panic__"),
+                ANYARGS::from_arity_1(__test_rb_method_panic__),
+                0
+            );
+
+            rb_define_method(
+                rb_mKernel,
+                cstr!("__test_rb_method_panic_arg_error__"),
+                ANYARGS::from_arity_2(__test_rb_method_panic_arg_error__),
+                1
+            );
+        }
+
+        assert.rb_eq(lazy_eval("__test_rb_method_ok__(:ok)"), unsafe { rb_id2sym(rb_intern(cstr!("ok"))) });
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_rb_method_panic__
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eRuntimeError }
+        );
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_rb_method_panic__
+                rescue Exception => e
+                e.message
+                end
+            "#),
+            "__test_rb_method_panic__ panicked".to_ruby()
+        );
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_rb_method_panic_arg_error__(42)
+                rescue Exception => e
+                e.class
+                end
+            "#),
+            unsafe { rb_eArgError }
+        );
+
+        assert.rb_eq(
+            lazy_eval(r#"
+                begin
+                __test_rb_method_panic_arg_error__(42)
+                rescue Exception => e
+                e.message
+                end
+            "#),
+            "__test_rb_method_panic_arg_error__ panicked with 42".to_ruby()
+        );
+    }
+}